@@ -0,0 +1,87 @@
+use super::{attribute_set, enable, Adapter};
+use crate::parser::NixVariable;
+
+/// Top-level keys that only appear in `languages.toml`.
+const LANGUAGE_KEYS: [&str; 4] = ["language", "language-server", "grammar", "use-grammars"];
+
+/// Adapter for the Helix `config.toml` and `languages.toml`, which go to
+/// `settings` and `languages` respectively.
+pub struct HelixAdapter {}
+
+impl Default for HelixAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HelixAdapter {
+    pub fn new() -> HelixAdapter {
+        HelixAdapter {}
+    }
+}
+
+impl Adapter for HelixAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let option = match values
+            .iter()
+            .any(|variable| LANGUAGE_KEYS.contains(&variable.name.as_str()))
+        {
+            true => "programs.helix.languages",
+            false => "programs.helix.settings",
+        };
+        vec![
+            enable("helix"),
+            NixVariable::new(option, &attribute_set(values)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{toml::TomlParser, ExpressionGenerator, Parser};
+
+    fn generate(content: &str) -> Option<String> {
+        ExpressionGenerator::new()
+            .with_adapters(AdapterRegistry::builtin())
+            .generate_nix_expression("helix", &TomlParser::new().parse(content).unwrap())
+    }
+
+    #[test]
+    fn test_helix() {
+        assert_eq!(
+            generate("theme = \"nord\"\n[editor]\nline-number = \"relative\"\n"),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.helix.enable = true;
+programs.helix.settings = {
+editor = {
+line-number = \"relative\";
+};
+theme = \"nord\";
+};
+}"
+                .to_string()
+            )
+        );
+        assert_eq!(
+            generate("[[language]]\nname = \"nix\"\nauto-format = true\n"),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.helix.enable = true;
+programs.helix.languages = {
+language = [
+{
+auto-format = true;
+name = \"nix\";
+}
+];
+};
+}"
+                .to_string()
+            )
+        );
+    }
+}
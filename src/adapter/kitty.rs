@@ -0,0 +1,192 @@
+use super::{enable, Adapter};
+use crate::converter::plain_string;
+use crate::parser::{NixVariable, NixVariableValue};
+use indexmap::IndexMap;
+
+/// Adapter for `kitty.conf` parsed as `keyword-value`.
+///
+/// The font is moved to `font`, `map` to `keybindings` and `env` to
+/// `environment`. Includes and repeated options keep their order in
+/// `extraConfig`, everything else becomes a typed setting. home-manager
+/// writes `extraConfig` last, so everything following the first include goes
+/// there as well, as the included file would override it otherwise.
+pub struct KittyAdapter {}
+
+impl Default for KittyAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KittyAdapter {
+    pub fn new() -> KittyAdapter {
+        KittyAdapter {}
+    }
+
+    fn typed(value: &str) -> NixVariableValue {
+        match value {
+            "yes" => NixVariableValue::Boolean(true),
+            "no" => NixVariableValue::Boolean(false),
+            value => match value.parse::<f64>() {
+                Ok(n) if value.starts_with(|c: char| c.is_ascii_digit()) => {
+                    NixVariableValue::Number(n)
+                }
+                _ => NixVariableValue::String(value.to_string()),
+            },
+        }
+    }
+}
+
+impl Adapter for KittyAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let mut font = None;
+        let mut settings = IndexMap::new();
+        let mut keybindings = IndexMap::new();
+        let mut environment = IndexMap::new();
+        let mut extra_config = vec![];
+        let has_font = values.iter().any(|v| v.name == "font_family");
+        let mut included = false;
+        for variable in values {
+            let lines: Vec<String> = match &variable.value {
+                NixVariableValue::List(l) => l.iter().map(plain_string).collect(),
+                value => vec![plain_string(value)],
+            };
+            included |= variable.name == "include";
+            match (variable.name.as_str(), lines.as_slice()) {
+                (name, _) if included => {
+                    extra_config.extend(lines.iter().map(|line| format!("{} {}", name, line)))
+                }
+                ("font_family", [.., family]) => font = Some(family.to_owned()),
+                ("map", _) => keybindings.extend(lines.iter().filter_map(|line| {
+                    let (keys, action) = line.split_once(char::is_whitespace)?;
+                    Some((
+                        keys.to_string(),
+                        NixVariableValue::String(action.trim().to_string()),
+                    ))
+                })),
+                ("env", _) => environment.extend(lines.iter().filter_map(|line| {
+                    let (name, value) = line.split_once('=')?;
+                    Some((
+                        name.trim().to_string(),
+                        NixVariableValue::String(value.trim().to_string()),
+                    ))
+                })),
+                ("font_size", [size]) if has_font => {
+                    settings.insert("font_size".to_string(), KittyAdapter::typed(size));
+                }
+                (name, [value]) if name != "include" => {
+                    settings.insert(name.to_string(), KittyAdapter::typed(value));
+                }
+                (name, _) => {
+                    extra_config.extend(lines.iter().map(|line| format!("{} {}", name, line)))
+                }
+            }
+        }
+
+        let mut variables = vec![enable("kitty")];
+        if let Some(font) = font {
+            variables.push(NixVariable::new(
+                "programs.kitty.font.name",
+                &NixVariableValue::String(font),
+            ));
+            if let Some(size) = settings.shift_remove("font_size") {
+                variables.push(NixVariable::new("programs.kitty.font.size", &size));
+            }
+        }
+        for (option, value) in [
+            ("settings", settings),
+            ("keybindings", keybindings),
+            ("environment", environment),
+        ] {
+            if !value.is_empty() {
+                variables.push(NixVariable::new(
+                    &format!("programs.kitty.{}", option),
+                    &NixVariableValue::AttributeSet(value),
+                ));
+            }
+        }
+        if !extra_config.is_empty() {
+            variables.push(NixVariable::new(
+                "programs.kitty.extraConfig",
+                &NixVariableValue::IndentedString(extra_config.join("\n") + "\n"),
+            ));
+        }
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{keyword::KeywordValueParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_kitty() {
+        let parsed = KeywordValueParser::new().parse(KITTY_CONF).unwrap();
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_adapters(AdapterRegistry::builtin())
+                .generate_nix_expression("kitty", &parsed),
+            Some(EXPECTED.to_string())
+        );
+        // The included file must not override the options following it
+        let parsed = KeywordValueParser::new()
+            .parse("include themes/nord.conf\nbackground_opacity 0.9\n")
+            .unwrap();
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_adapters(AdapterRegistry::builtin())
+                .generate_nix_expression("kitty", &parsed),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.kitty.enable = true;
+programs.kitty.extraConfig = ''
+include themes/nord.conf
+background_opacity 0.9
+'';
+}"
+                .to_string()
+            )
+        );
+    }
+
+    const KITTY_CONF: &str = "
+# vim:fileencoding=utf-8
+font_family      JetBrains Mono
+font_size 11.5
+background_opacity 0.9
+enable_audio_bell no
+map ctrl+shift+enter new_window_with_cwd
+map ctrl+shift+t     new_tab_with_cwd
+env EDITOR=nvim
+include themes/nord.conf
+cursor #d8dee9
+symbol_map U+E0A0-U+E0A3 Symbols Nerd Font
+symbol_map U+F000-U+F2E0 Symbols Nerd Font
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+programs.kitty.enable = true;
+programs.kitty.font.name = \"JetBrains Mono\";
+programs.kitty.font.size = 11.5;
+programs.kitty.settings = {
+background_opacity = 0.9;
+enable_audio_bell = false;
+};
+programs.kitty.keybindings = {
+\"ctrl+shift+enter\" = \"new_window_with_cwd\";
+\"ctrl+shift+t\" = \"new_tab_with_cwd\";
+};
+programs.kitty.environment = {
+EDITOR = \"nvim\";
+};
+programs.kitty.extraConfig = ''
+include themes/nord.conf
+cursor #d8dee9
+symbol_map U+E0A0-U+E0A3 Symbols Nerd Font
+symbol_map U+F000-U+F2E0 Symbols Nerd Font
+'';
+}";
+}
@@ -0,0 +1,230 @@
+pub mod helix;
+pub mod kitty;
+pub mod neovim;
+pub mod rules;
+pub mod tmux;
+pub mod waybar;
+
+use crate::parser::{NixVariable, NixVariableValue};
+use indexmap::IndexMap;
+
+/// Maps the parsed configuration of a program onto the options of its
+/// home-manager module, for programs whose module reshapes the configuration.
+pub trait Adapter {
+    /// Returns variables named after full option paths, like the output of a
+    /// [`Converter`](crate::converter::Converter).
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable>;
+}
+
+/// Adapters keyed by program name, consulted by the
+/// [`ExpressionGenerator`](crate::parser::ExpressionGenerator).
+pub struct AdapterRegistry {
+    adapters: IndexMap<String, Box<dyn Adapter>>,
+}
+
+impl Default for AdapterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdapterRegistry {
+    pub fn new() -> AdapterRegistry {
+        AdapterRegistry {
+            adapters: IndexMap::new(),
+        }
+    }
+
+    /// The registry with an adapter for every supported program.
+    pub fn builtin() -> AdapterRegistry {
+        AdapterRegistry::new()
+            .add_adapter("alacritty", Box::new(SettingsAdapter::new("alacritty")))
+            .add_adapter("dunst", Box::new(SettingsAdapter::new("dunst")))
+            .add_adapter("helix", Box::new(helix::HelixAdapter::new()))
+            .add_adapter("kitty", Box::new(kitty::KittyAdapter::new()))
+            .add_adapter("neovim", Box::new(neovim::NeovimAdapter::new()))
+            .add_adapter("starship", Box::new(SettingsAdapter::new("starship")))
+            .add_adapter("tmux", Box::new(tmux::TmuxAdapter::new()))
+            .add_adapter("waybar", Box::new(waybar::WaybarAdapter::new()))
+            .add_adapter("zellij", Box::new(SettingsAdapter::new("zellij")))
+    }
+
+    /// Registers an adapter, replacing an existing one for the program.
+    pub fn add_adapter(mut self, program: &str, adapter: Box<dyn Adapter>) -> AdapterRegistry {
+        self.adapters.insert(program.to_string(), adapter);
+        self
+    }
+
+    /// Registers the adapters of a user rules file, see
+    /// [`RulesAdapter`](rules::RulesAdapter). They replace built-in adapters
+    /// of the same program.
+    pub fn with_rules(self, content: &str) -> Option<AdapterRegistry> {
+        Some(rules::RulesAdapter::parse_rules(content)?.into_iter().fold(
+            self,
+            |registry, adapter| {
+                let program = adapter.program().to_string();
+                registry.add_adapter(&program, Box::new(adapter))
+            },
+        ))
+    }
+
+    pub fn get(&self, program: &str) -> Option<&dyn Adapter> {
+        self.adapters.get(program).map(|adapter| adapter.as_ref())
+    }
+}
+
+/// `programs.<program>.enable = true`.
+pub(crate) fn enable(program: &str) -> NixVariable {
+    NixVariable::new(
+        &format!("programs.{}.enable", program),
+        &NixVariableValue::Boolean(true),
+    )
+}
+
+/// Collects variables into an attribute set.
+pub(crate) fn attribute_set(values: &[NixVariable]) -> NixVariableValue {
+    NixVariableValue::AttributeSet(
+        values
+            .iter()
+            .map(|variable| (variable.name.to_owned(), variable.value.to_owned()))
+            .collect(),
+    )
+}
+
+/// Adapter for modules that take the whole configuration as `settings`, like
+/// Alacritty, Starship, Zellij or Dunst.
+pub struct SettingsAdapter {
+    program: String,
+}
+
+impl SettingsAdapter {
+    pub fn new(program: &str) -> SettingsAdapter {
+        SettingsAdapter {
+            program: program.to_string(),
+        }
+    }
+}
+
+impl Adapter for SettingsAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        vec![
+            enable(&self.program),
+            NixVariable::new(
+                &format!("programs.{}.settings", self.program),
+                &attribute_set(values),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{
+        ini::IniParser, toml::TomlParser, yaml::YamlParser, ExpressionGenerator, Parser,
+    };
+
+    fn generate(program: &str, parser: &dyn Parser, content: &str) -> String {
+        ExpressionGenerator::new()
+            .with_adapters(AdapterRegistry::builtin())
+            .generate_nix_expression(program, &parser.parse(content).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_alacritty() {
+        assert_eq!(
+            generate(
+                "alacritty",
+                &TomlParser::new(),
+                "[font]\nsize = 12\n[window]\nopacity = 0.9\n"
+            ),
+            "{ config, pkgs, ... }:
+{
+programs.alacritty.enable = true;
+programs.alacritty.settings = {
+font = {
+size = 12;
+};
+window = {
+opacity = 0.9;
+};
+};
+}"
+        );
+    }
+
+    #[test]
+    fn test_starship() {
+        assert_eq!(
+            generate(
+                "starship",
+                &TomlParser::new(),
+                "add_newline = false\n[character]\nsuccess_symbol = \"[➜](bold green)\"\n"
+            ),
+            "{ config, pkgs, ... }:
+{
+programs.starship.enable = true;
+programs.starship.settings = {
+add_newline = false;
+character = {
+success_symbol = \"[➜](bold green)\";
+};
+};
+}"
+        );
+    }
+
+    #[test]
+    fn test_zellij() {
+        assert_eq!(
+            generate(
+                "zellij",
+                &YamlParser::new(),
+                "theme: nord\ndefault_layout: compact\n"
+            ),
+            "{ config, pkgs, ... }:
+{
+programs.zellij.enable = true;
+programs.zellij.settings = {
+theme = \"nord\";
+default_layout = \"compact\";
+};
+}"
+        );
+    }
+
+    #[test]
+    fn test_dunst() {
+        assert_eq!(
+            generate(
+                "dunst",
+                &IniParser::new(),
+                "[global]\nfont = Monospace 10\noffset = 10x50\n\n[urgency_low]\ntimeout = 5\n"
+            ),
+            "{ config, pkgs, ... }:
+{
+programs.dunst.enable = true;
+programs.dunst.settings = {
+global = {
+font = \"Monospace 10\";
+offset = \"10x50\";
+};
+urgency_low = {
+timeout = 5;
+};
+};
+}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_program() {
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_adapters(AdapterRegistry::builtin())
+                .generate_nix_expression("foo", &[]),
+            Some("{ config, pkgs, ... }:\n{\nprograms.foo.enable = true;\n};\n}".to_string())
+        );
+    }
+}
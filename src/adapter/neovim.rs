@@ -0,0 +1,103 @@
+use super::{enable, Adapter};
+use crate::converter::plain_string;
+use crate::parser::{attr_name, NixVariable, NixVariableValue};
+
+/// Adapter for lists of Neovim plugins, which go to `programs.neovim.plugins`
+/// from `pkgs.vimPlugins`.
+///
+/// Both the `lazy-lock.json` of lazy.nvim, keyed by plugin name, and plain
+/// lists of `owner/repo` strings are understood. The attribute names follow
+/// the nixpkgs convention, e.g. `telescope.nvim` becomes `telescope-nvim`.
+pub struct NeovimAdapter {}
+
+impl Default for NeovimAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NeovimAdapter {
+    pub fn new() -> NeovimAdapter {
+        NeovimAdapter {}
+    }
+
+    fn plugin(name: &str) -> NixVariableValue {
+        let name = name.rsplit('/').next().unwrap_or(name);
+        NixVariableValue::Expression(attr_name(&name.to_lowercase().replace('.', "-")))
+    }
+}
+
+impl Adapter for NeovimAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let plugins = match values {
+            [NixVariable {
+                name,
+                value: NixVariableValue::List(items),
+            }] if name == "items" => items
+                .iter()
+                .map(|item| NeovimAdapter::plugin(&plain_string(item)))
+                .collect(),
+            variables => variables
+                .iter()
+                .map(|variable| NeovimAdapter::plugin(&variable.name))
+                .collect(),
+        };
+        vec![
+            enable("neovim"),
+            NixVariable::new(
+                "programs.neovim.plugins",
+                &NixVariableValue::With(
+                    "pkgs.vimPlugins".to_string(),
+                    Box::new(NixVariableValue::List(plugins)),
+                ),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+
+    fn generate(content: &str) -> Option<String> {
+        ExpressionGenerator::new()
+            .with_adapters(AdapterRegistry::builtin())
+            .generate_nix_expression("neovim", &JsonParser::new().parse(content).unwrap())
+    }
+
+    #[test]
+    fn test_neovim() {
+        assert_eq!(generate(LAZY_LOCK), Some(EXPECTED.to_string()));
+        assert_eq!(
+            generate("[\"nvim-lualine/lualine.nvim\", \"tpope/vim-fugitive\"]"),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.neovim.enable = true;
+programs.neovim.plugins = with pkgs.vimPlugins; [
+lualine-nvim
+vim-fugitive
+];
+}"
+                .to_string()
+            )
+        );
+    }
+
+    const LAZY_LOCK: &str = "{
+  \"lazy.nvim\": { \"branch\": \"main\", \"commit\": \"aedcd79811d491b60d0a6577a9c1701063c2a609\" },
+  \"nvim-treesitter\": { \"branch\": \"master\", \"commit\": \"f197a15b0d1e8d555263af20add51450e5aaa1f0\" },
+  \"telescope.nvim\": { \"branch\": \"master\", \"commit\": \"a0bbec21143c7bc5f8bb02e0005fa0b982edc026\" }
+}";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+programs.neovim.enable = true;
+programs.neovim.plugins = with pkgs.vimPlugins; [
+lazy-nvim
+nvim-treesitter
+telescope-nvim
+];
+}";
+}
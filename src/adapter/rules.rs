@@ -0,0 +1,271 @@
+use super::{enable, Adapter};
+use crate::converter::plain_string;
+use crate::parser::{
+    nix::NixParser, quote_string, toml::TomlParser, NixVariable, NixVariableValue, Parser,
+};
+use indexmap::IndexMap;
+
+/// Where the configuration left after moving and dropping keys goes.
+#[derive(Debug, Clone, PartialEq)]
+enum Destination {
+    /// An option taking the configuration as attribute set, e.g.
+    /// `programs.mytool.settings`.
+    Option(String),
+    /// A file in `xdg.configFile` generated with `pkgs.formats`.
+    ConfigFile { path: String, format: String },
+}
+
+/// Adapter defined by the user in a rules file, for programs without a
+/// built-in adapter.
+///
+/// A rules file is a TOML or Nix attribute set with a set of rules per
+/// program:
+///
+/// ```toml
+/// [mytool]
+/// destination = "xdg.configFile" # defaults to programs.mytool.settings
+/// file = "mytool/config.json"    # the format follows from the extension
+/// drop = ["cache.dir"]
+/// defaults = ["theme"]
+///
+/// [mytool.move]
+/// "server.port" = "services.mytool.port"
+/// ```
+///
+/// Key paths refer to the parsed configuration. Values listed in `defaults`
+/// are wrapped in `lib.mkDefault`, also when they are moved. Files in
+/// `xdg.configFile` are no options, so only moved values are wrapped for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulesAdapter {
+    program: String,
+    destination: Destination,
+    moves: IndexMap<String, String>,
+    drop: Vec<String>,
+    defaults: Vec<String>,
+}
+
+impl RulesAdapter {
+    /// Reads the rules of every program in a rules file.
+    pub fn parse_rules(content: &str) -> Option<Vec<RulesAdapter>> {
+        TomlParser::new()
+            .parse(content)
+            .or_else(|| NixParser::new().parse(content))?
+            .iter()
+            .map(|program| match &program.value {
+                NixVariableValue::AttributeSet(rules) => RulesAdapter::new(&program.name, rules),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn new(program: &str, rules: &IndexMap<String, NixVariableValue>) -> Option<RulesAdapter> {
+        let string = |key: &str| match rules.get(key) {
+            Some(NixVariableValue::String(s)) => Some(Some(s.to_owned())),
+            Some(_) => None,
+            None => Some(None),
+        };
+        let strings = |key: &str| match rules.get(key) {
+            Some(NixVariableValue::List(l)) => Some(l.iter().map(plain_string).collect()),
+            Some(_) => None,
+            None => Some(vec![]),
+        };
+        let destination = match (string("destination")?, string("file")?) {
+            (Some(destination), Some(path)) if destination == "xdg.configFile" => {
+                let format = match string("format")? {
+                    Some(format) => format,
+                    None => path.rsplit_once('.')?.1.to_string(),
+                };
+                if !["json", "toml", "yaml", "ini"].contains(&format.as_str()) {
+                    return None;
+                }
+                Destination::ConfigFile { path, format }
+            }
+            (Some(destination), None) if destination != "xdg.configFile" => {
+                Destination::Option(destination)
+            }
+            (None, None) => Destination::Option(format!("programs.{}.settings", program)),
+            _ => return None,
+        };
+        let moves = match rules.get("move") {
+            Some(NixVariableValue::AttributeSet(moves)) => moves
+                .iter()
+                .map(|(key, option)| match option {
+                    NixVariableValue::String(option) => Some((key.to_owned(), option.to_owned())),
+                    _ => None,
+                })
+                .collect::<Option<IndexMap<String, String>>>()?,
+            Some(_) => return None,
+            None => IndexMap::new(),
+        };
+        Some(RulesAdapter {
+            program: program.to_string(),
+            destination,
+            moves,
+            drop: strings("drop")?,
+            defaults: strings("defaults")?,
+        })
+    }
+
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// Whether the value at a key path ends up in an option, which can have
+    /// a priority, instead of a generated file.
+    fn has_priority(&self, path: &str) -> bool {
+        matches!(self.destination, Destination::Option(_))
+            || self
+                .moves
+                .keys()
+                .any(|moved| path == moved || path.starts_with(&format!("{}.", moved)))
+    }
+
+    /// Returns the value at a dotted key path.
+    fn get_mut<'a>(
+        set: &'a mut IndexMap<String, NixVariableValue>,
+        path: &str,
+    ) -> Option<&'a mut NixVariableValue> {
+        let (key, rest) = match path.split_once('.') {
+            Some((key, rest)) => (key, Some(rest)),
+            None => (path, None),
+        };
+        match (set.get_mut(key)?, rest) {
+            (value, None) => Some(value),
+            (NixVariableValue::AttributeSet(nested), Some(rest)) => {
+                RulesAdapter::get_mut(nested, rest)
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the value at a dotted key path.
+    fn take(set: &mut IndexMap<String, NixVariableValue>, path: &str) -> Option<NixVariableValue> {
+        match path.rsplit_once('.') {
+            Some((parent, key)) => match RulesAdapter::get_mut(set, parent)? {
+                NixVariableValue::AttributeSet(parent) => parent.shift_remove(key),
+                _ => None,
+            },
+            None => set.shift_remove(path),
+        }
+    }
+}
+
+impl Adapter for RulesAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let mut config: IndexMap<String, NixVariableValue> = values
+            .iter()
+            .map(|variable| (variable.name.to_owned(), variable.value.to_owned()))
+            .collect();
+        for path in self.defaults.iter().filter(|path| self.has_priority(path)) {
+            if let Some(value) = RulesAdapter::get_mut(&mut config, path) {
+                *value =
+                    NixVariableValue::Function("lib.mkDefault".to_string(), vec![value.to_owned()]);
+            }
+        }
+        for path in &self.drop {
+            RulesAdapter::take(&mut config, path);
+        }
+
+        let mut variables = vec![];
+        let destination = match &self.destination {
+            Destination::Option(option) => option.to_owned(),
+            Destination::ConfigFile { path, .. } => {
+                format!("xdg.configFile.{}.source", quote_string(path))
+            }
+        };
+        if destination.starts_with(&format!("programs.{}.", self.program)) {
+            variables.push(enable(&self.program));
+        }
+        for (path, option) in &self.moves {
+            if let Some(value) = RulesAdapter::take(&mut config, path) {
+                variables.push(NixVariable::new(option, &value));
+            }
+        }
+        if config.is_empty() {
+            return variables;
+        }
+        let config = NixVariableValue::AttributeSet(config);
+        let value = match &self.destination {
+            Destination::Option(_) => config,
+            Destination::ConfigFile { path, format } => NixVariableValue::Function(
+                format!("(pkgs.formats.{} {{ }}).generate", format),
+                vec![
+                    NixVariableValue::String(path.rsplit('/').next().unwrap_or(path).to_string()),
+                    config,
+                ],
+            ),
+        };
+        variables.push(NixVariable::new(&destination, &value));
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+
+    fn generate(rules: &str, program: &str) -> Option<String> {
+        ExpressionGenerator::new()
+            .with_adapters(AdapterRegistry::builtin().with_rules(rules)?)
+            .generate_nix_expression(program, &JsonParser::new().parse(CONFIG).unwrap())
+    }
+
+    #[test]
+    fn test_toml_rules() {
+        assert_eq!(
+            generate(
+                "[mytool]\ndrop = [\"cache\"]\ndefaults = [\"theme\", \"server.port\"]\n\n[mytool.move]\n\"server.port\" = \"services.mytool.port\"\n",
+                "mytool"
+            ),
+            Some(
+                "{ config, lib, pkgs, ... }:
+{
+programs.mytool.enable = true;
+services.mytool.port = lib.mkDefault 8080;
+programs.mytool.settings = {
+server = {
+host = \"localhost\";
+};
+theme = lib.mkDefault \"dark\";
+};
+}"
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_nix_rules() {
+        assert_eq!(
+            generate(
+                "{ mytool = { destination = \"xdg.configFile\"; file = \"mytool/config.json\"; drop = [ \"cache\" ]; defaults = [ \"theme\" \"server.port\" ]; move = { server = \"services.mytool.server\"; }; }; }",
+                "mytool"
+            ),
+            Some(
+                "{ config, lib, pkgs, ... }:
+{
+services.mytool.server = {
+host = \"localhost\";
+port = lib.mkDefault 8080;
+};
+xdg.configFile.\"mytool/config.json\".source = (pkgs.formats.json { }).generate \"config.json\" {
+theme = \"dark\";
+};
+}"
+                .to_string()
+            )
+        );
+        assert!(generate(
+            "{ mytool = { destination = \"xdg.configFile\"; }; }",
+            "mytool"
+        )
+        .is_none());
+    }
+
+    const CONFIG: &str = "{
+    \"cache\": \"/tmp/mytool\",
+    \"server\": { \"host\": \"localhost\", \"port\": 8080 },
+    \"theme\": \"dark\"
+}";
+}
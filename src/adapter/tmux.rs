@@ -0,0 +1,165 @@
+use super::{enable, Adapter};
+use crate::converter::{plain_string, split_words};
+use crate::parser::{NixVariable, NixVariableValue};
+
+/// Global tmux options with a dedicated option in `programs.tmux`.
+const OPTIONS: [(&str, &str); 9] = [
+    ("prefix", "prefix"),
+    ("base-index", "baseIndex"),
+    ("escape-time", "escapeTime"),
+    ("history-limit", "historyLimit"),
+    ("mode-keys", "keyMode"),
+    ("mouse", "mouse"),
+    ("default-terminal", "terminal"),
+    ("default-shell", "shell"),
+    ("aggressive-resize", "aggressiveResize"),
+];
+
+/// Adapter for `tmux.conf` parsed as `tmux-conf`.
+///
+/// Global options set once with `set -g`, `set -sg` or `setw -g` that have an
+/// option in `programs.tmux` are moved there. All other commands, including
+/// options set several times, are kept in `extraConfig` in the order of the
+/// file, as later commands may override earlier ones or files they source.
+pub struct TmuxAdapter {}
+
+impl Default for TmuxAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TmuxAdapter {
+    pub fn new() -> TmuxAdapter {
+        TmuxAdapter {}
+    }
+
+    /// Returns the module option and value of a `set` command.
+    fn option(arguments: &str) -> Option<(&'static str, NixVariableValue)> {
+        let words = split_words(arguments);
+        let (flags, words): (Vec<&String>, Vec<&String>) =
+            words.iter().partition(|word| word.starts_with('-'));
+        // Only global and server options, not those of a session or window
+        if flags.iter().any(|flag| {
+            !flag
+                .trim_start_matches('-')
+                .chars()
+                .all(|c| "gs".contains(c))
+        }) {
+            return None;
+        }
+        let [name, value] = words.as_slice() else {
+            return None;
+        };
+        let (_, option) = OPTIONS.iter().find(|(n, _)| n == name)?;
+        let value = match (*option, value.as_str()) {
+            ("mouse" | "aggressiveResize", "on") => NixVariableValue::Boolean(true),
+            ("mouse" | "aggressiveResize", "off") => NixVariableValue::Boolean(false),
+            ("baseIndex" | "escapeTime" | "historyLimit", value) => {
+                NixVariableValue::Number(value.parse::<u32>().ok()? as f64)
+            }
+            ("mouse" | "aggressiveResize", _) => return None,
+            (_, value) => NixVariableValue::String(value.to_string()),
+        };
+        Some((option, value))
+    }
+}
+
+impl Adapter for TmuxAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let mut variables = vec![enable("tmux")];
+        let mut extra_config = vec![];
+        let commands: Vec<(String, Option<(&str, NixVariableValue)>)> = values
+            .iter()
+            .flat_map(|variable| {
+                let lines: Vec<String> = match &variable.value {
+                    NixVariableValue::List(l) => l.iter().map(plain_string).collect(),
+                    value => vec![plain_string(value)],
+                };
+                lines.into_iter().map(|line| {
+                    let option = match variable.name.as_str() {
+                        "set" | "set-option" | "setw" | "set-window-option" => {
+                            TmuxAdapter::option(&line)
+                        }
+                        _ => None,
+                    };
+                    (format!("{} {}", variable.name, line), option)
+                })
+            })
+            .collect();
+        let count = |option: &str| {
+            commands
+                .iter()
+                .filter(|(_, other)| matches!(other, Some((other, _)) if *other == option))
+                .count()
+        };
+        for (command, option) in &commands {
+            match option {
+                Some((option, value)) if count(option) == 1 => variables.push(NixVariable::new(
+                    &format!("programs.tmux.{}", option),
+                    value,
+                )),
+                _ => extra_config.push(command.to_owned()),
+            }
+        }
+        if !extra_config.is_empty() {
+            variables.push(NixVariable::new(
+                "programs.tmux.extraConfig",
+                &NixVariableValue::IndentedString(extra_config.join("\n") + "\n"),
+            ));
+        }
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{keyword::KeywordValueParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_tmux() {
+        let parsed = KeywordValueParser::new()
+            .with_order_kept()
+            .parse(TMUX_CONF)
+            .unwrap();
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_adapters(AdapterRegistry::builtin())
+                .generate_nix_expression("tmux", &parsed),
+            Some(EXPECTED.to_string())
+        );
+    }
+
+    const TMUX_CONF: &str = "
+set -g prefix C-a
+set -g mouse on
+set -g default-terminal \"tmux-256color\"
+set -sg escape-time 10
+setw -g mode-keys vi
+set -g status-style bg=default
+source-file ~/.tmux.local
+set -g mouse off
+bind r source-file ~/.config/tmux/tmux.conf
+set -g status-style bg=black
+bind | split-window -h
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+programs.tmux.enable = true;
+programs.tmux.prefix = \"C-a\";
+programs.tmux.terminal = \"tmux-256color\";
+programs.tmux.escapeTime = 10;
+programs.tmux.keyMode = \"vi\";
+programs.tmux.extraConfig = ''
+set -g mouse on
+set -g status-style bg=default
+source-file ~/.tmux.local
+set -g mouse off
+bind r source-file ~/.config/tmux/tmux.conf
+set -g status-style bg=black
+bind | split-window -h
+'';
+}";
+}
@@ -0,0 +1,96 @@
+use super::{attribute_set, enable, Adapter};
+use crate::parser::{NixVariable, NixVariableValue};
+
+/// Adapter for the Waybar configuration. A single bar becomes
+/// `settings.mainBar`, an array of bars is used as `settings` as is.
+pub struct WaybarAdapter {}
+
+impl Default for WaybarAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WaybarAdapter {
+    pub fn new() -> WaybarAdapter {
+        WaybarAdapter {}
+    }
+}
+
+impl Adapter for WaybarAdapter {
+    fn adapt(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let settings = match values {
+            [NixVariable {
+                name,
+                value: value @ NixVariableValue::List(_),
+            }] if name == "items" => NixVariable::new("programs.waybar.settings", value),
+            values => NixVariable::new("programs.waybar.settings.mainBar", &attribute_set(values)),
+        };
+        vec![enable("waybar"), settings]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+
+    fn generate(content: &str) -> Option<String> {
+        ExpressionGenerator::new()
+            .with_adapters(AdapterRegistry::builtin())
+            .generate_nix_expression(
+                "waybar",
+                &JsonParser::new().with_comments().parse(content).unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_waybar() {
+        assert_eq!(
+            generate(WAYBAR),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.waybar.enable = true;
+programs.waybar.settings.mainBar = {
+height = 30;
+layer = \"top\";
+modules-left = [
+\"sway/workspaces\"
+];
+\"sway/workspaces\" = {
+disable-scroll = true;
+};
+};
+}"
+                .to_string()
+            )
+        );
+        assert_eq!(
+            generate("[{ \"output\": \"DP-1\" }, { \"output\": \"HDMI-A-1\" }]"),
+            Some(
+                "{ config, pkgs, ... }:
+{
+programs.waybar.enable = true;
+programs.waybar.settings = [
+{
+output = \"DP-1\";
+}
+{
+output = \"HDMI-A-1\";
+}
+];
+}"
+                .to_string()
+            )
+        );
+    }
+
+    const WAYBAR: &str = "{
+    // Top bar
+    \"layer\": \"top\",
+    \"height\": 30,
+    \"modules-left\": [\"sway/workspaces\"],
+    \"sway/workspaces\": { \"disable-scroll\": true },
+}";
+}
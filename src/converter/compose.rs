@@ -0,0 +1,548 @@
+use super::{plain_string, split_words, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Compose service keys that turn into a single `extraOptions` flag per value.
+const FLAGS: [(&str, &str); 6] = [
+    ("cap_add", "--cap-add"),
+    ("cap_drop", "--cap-drop"),
+    ("devices", "--device"),
+    ("dns", "--dns"),
+    ("extra_hosts", "--add-host"),
+    ("shm_size", "--shm-size"),
+];
+
+/// Healthcheck keys and the matching container runtime flags.
+const HEALTHCHECK_FLAGS: [(&str, &str); 5] = [
+    ("test", "--health-cmd"),
+    ("interval", "--health-interval"),
+    ("timeout", "--health-timeout"),
+    ("retries", "--health-retries"),
+    ("start_period", "--health-start-period"),
+];
+
+/// Converts a Docker Compose file into `virtualisation.oci-containers`
+/// containers. Compose keys without an equivalent are reported as warnings.
+pub struct ComposeConverter {}
+
+impl Default for ComposeConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComposeConverter {
+    pub fn new() -> ComposeConverter {
+        ComposeConverter {}
+    }
+
+    /// Returns a scalar or a list of scalars as strings.
+    fn strings(value: &NixVariableValue) -> Vec<String> {
+        match value {
+            NixVariableValue::List(l) => l.iter().map(plain_string).collect(),
+            NixVariableValue::Null => vec![],
+            value => vec![plain_string(value)],
+        }
+    }
+
+    /// Returns the keys of a mapping or the entries of a list.
+    fn names(value: &NixVariableValue) -> Vec<String> {
+        match value {
+            NixVariableValue::AttributeSet(a) => a.keys().cloned().collect(),
+            value => ComposeConverter::strings(value),
+        }
+    }
+
+    fn string_list(strings: Vec<String>) -> NixVariableValue {
+        NixVariableValue::List(strings.into_iter().map(NixVariableValue::String).collect())
+    }
+
+    /// Turns the long syntax of ports and volumes into the short syntax.
+    fn short_syntax(value: &NixVariableValue, keys: &[&str], suffix: &str) -> String {
+        match value {
+            NixVariableValue::AttributeSet(a) => {
+                let short = keys
+                    .iter()
+                    .filter_map(|key| a.get(*key).map(plain_string))
+                    .collect::<Vec<String>>()
+                    .join(":");
+                match a.get(suffix) {
+                    Some(NixVariableValue::Boolean(true)) => format!("{}:ro", short),
+                    Some(NixVariableValue::String(protocol)) => format!("{}/{}", short, protocol),
+                    _ => short,
+                }
+            }
+            value => plain_string(value),
+        }
+    }
+
+    fn convert_service(
+        name: &str,
+        service: &IndexMap<String, NixVariableValue>,
+        containers: &IndexMap<String, String>,
+        warnings: &mut Vec<String>,
+    ) -> IndexMap<String, NixVariableValue> {
+        let mut options = IndexMap::new();
+        let mut cmd = vec![];
+        let mut extra_options = vec![];
+        for (key, value) in service {
+            match key.as_str() {
+                "image" => {
+                    options.insert("image".to_string(), value.to_owned());
+                }
+                "ports" => {
+                    let ports = match value {
+                        NixVariableValue::List(l) => l
+                            .iter()
+                            .map(|port| {
+                                ComposeConverter::short_syntax(
+                                    port,
+                                    &["host_ip", "published", "target"],
+                                    "protocol",
+                                )
+                            })
+                            .collect(),
+                        value => ComposeConverter::strings(value),
+                    };
+                    options.insert("ports".to_string(), ComposeConverter::string_list(ports));
+                }
+                "volumes" => {
+                    let volumes = match value {
+                        NixVariableValue::List(l) => l
+                            .iter()
+                            .filter_map(|volume| match volume {
+                                NixVariableValue::AttributeSet(a)
+                                    if a.get("type").map(plain_string).as_deref()
+                                        == Some("tmpfs") =>
+                                {
+                                    warnings.push(format!(
+                                        "services.{}.volumes: the tmpfs at {} is not supported and was dropped",
+                                        name,
+                                        a.get("target").map(plain_string).unwrap_or_default()
+                                    ));
+                                    None
+                                }
+                                volume => Some(ComposeConverter::short_syntax(
+                                    volume,
+                                    &["source", "target"],
+                                    "read_only",
+                                )),
+                            })
+                            .collect(),
+                        value => ComposeConverter::strings(value),
+                    };
+                    if !volumes.is_empty() {
+                        options.insert(
+                            "volumes".to_string(),
+                            ComposeConverter::string_list(volumes),
+                        );
+                    }
+                }
+                "environment" => {
+                    let environment = match value {
+                        NixVariableValue::AttributeSet(a) => a
+                            .iter()
+                            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                            .collect::<Vec<(String, NixVariableValue)>>(),
+                        value => ComposeConverter::strings(value)
+                            .iter()
+                            .map(|assignment| match assignment.split_once('=') {
+                                Some((name, value)) => (
+                                    name.to_string(),
+                                    NixVariableValue::String(value.to_string()),
+                                ),
+                                None => (assignment.to_owned(), NixVariableValue::Null),
+                            })
+                            .collect(),
+                    };
+                    let mut variables = IndexMap::new();
+                    for (variable, value) in environment {
+                        match value {
+                            NixVariableValue::Null => warnings.push(format!(
+                                "services.{}.environment.{} has no value and was dropped",
+                                name, variable
+                            )),
+                            value => {
+                                variables.insert(
+                                    variable,
+                                    NixVariableValue::String(plain_string(&value)),
+                                );
+                            }
+                        }
+                    }
+                    options.insert(
+                        "environment".to_string(),
+                        NixVariableValue::AttributeSet(variables),
+                    );
+                }
+                "env_file" => {
+                    let files = match value {
+                        NixVariableValue::List(l) => l
+                            .iter()
+                            .map(|file| match file {
+                                NixVariableValue::AttributeSet(a) => {
+                                    a.get("path").map(plain_string).unwrap_or_default()
+                                }
+                                file => plain_string(file),
+                            })
+                            .collect(),
+                        value => ComposeConverter::strings(value),
+                    };
+                    options.insert(
+                        "environmentFiles".to_string(),
+                        ComposeConverter::string_list(files),
+                    );
+                }
+                "depends_on" => {
+                    // dependsOn only orders the containers, the long syntax
+                    // also waits for conditions
+                    if let NixVariableValue::AttributeSet(services) = value {
+                        for (service, dependency) in services {
+                            if let NixVariableValue::AttributeSet(keys) = dependency {
+                                warnings.extend(keys.iter().map(|(key, value)| {
+                                    format!(
+                                        "services.{}.depends_on.{}.{} ({}) is not supported by dependsOn and was dropped",
+                                        name,
+                                        service,
+                                        key,
+                                        plain_string(value)
+                                    )
+                                }));
+                            }
+                        }
+                    }
+                    let dependencies = ComposeConverter::names(value)
+                        .into_iter()
+                        .map(|service| containers.get(&service).cloned().unwrap_or(service))
+                        .collect();
+                    options.insert(
+                        "dependsOn".to_string(),
+                        ComposeConverter::string_list(dependencies),
+                    );
+                }
+                "command" => cmd.extend(match value {
+                    NixVariableValue::String(s) => split_words(s),
+                    value => ComposeConverter::strings(value),
+                }),
+                "entrypoint" => {
+                    let mut entrypoint = match value {
+                        NixVariableValue::String(s) => split_words(s),
+                        value => ComposeConverter::strings(value),
+                    };
+                    if !entrypoint.is_empty() {
+                        options.insert(
+                            "entrypoint".to_string(),
+                            NixVariableValue::String(entrypoint.remove(0)),
+                        );
+                    }
+                    // Further entrypoint arguments precede the command
+                    entrypoint.append(&mut cmd);
+                    cmd = entrypoint;
+                }
+                "user" => {
+                    options.insert(
+                        "user".to_string(),
+                        NixVariableValue::String(plain_string(value)),
+                    );
+                }
+                "working_dir" => {
+                    options.insert("workdir".to_string(), value.to_owned());
+                }
+                "labels" => {
+                    let labels = match value {
+                        NixVariableValue::AttributeSet(a) => a
+                            .iter()
+                            .map(|(label, value)| {
+                                (
+                                    label.to_owned(),
+                                    NixVariableValue::String(plain_string(value)),
+                                )
+                            })
+                            .collect(),
+                        value => ComposeConverter::strings(value)
+                            .iter()
+                            .filter_map(|label| label.split_once('='))
+                            .map(|(label, value)| {
+                                (
+                                    label.to_string(),
+                                    NixVariableValue::String(value.to_string()),
+                                )
+                            })
+                            .collect(),
+                    };
+                    options.insert("labels".to_string(), NixVariableValue::AttributeSet(labels));
+                }
+                "container_name" => {}
+                "hostname" => extra_options.push(format!("--hostname={}", plain_string(value))),
+                "privileged" => {
+                    if *value == NixVariableValue::Boolean(true) {
+                        extra_options.push("--privileged".to_string());
+                    }
+                }
+                "network_mode" => extra_options.push(format!("--network={}", plain_string(value))),
+                "networks" => extra_options.extend(
+                    ComposeConverter::names(value)
+                        .iter()
+                        .map(|network| format!("--network={}", network)),
+                ),
+                "healthcheck" => {
+                    let NixVariableValue::AttributeSet(healthcheck) = value else {
+                        continue;
+                    };
+                    for (key, value) in healthcheck {
+                        match HEALTHCHECK_FLAGS.iter().find(|(k, _)| k == key) {
+                            Some((_, flag)) => {
+                                let value = match value {
+                                    // The first entry is either CMD or CMD-SHELL
+                                    NixVariableValue::List(l) => l
+                                        .iter()
+                                        .skip(1)
+                                        .map(plain_string)
+                                        .collect::<Vec<String>>()
+                                        .join(" "),
+                                    value => plain_string(value),
+                                };
+                                extra_options.push(format!("{}={}", flag, value));
+                            }
+                            None => warnings.push(format!(
+                                "services.{}.healthcheck.{} is not supported and was dropped",
+                                name, key
+                            )),
+                        }
+                    }
+                }
+                "restart" => warnings.push(format!(
+                    "services.{}.restart was dropped, containers are restarted by systemd",
+                    name
+                )),
+                key => match FLAGS.iter().find(|(k, _)| *k == key) {
+                    Some((_, flag)) => extra_options.extend(
+                        ComposeConverter::strings(value)
+                            .iter()
+                            .map(|value| format!("{}={}", flag, value)),
+                    ),
+                    None => warnings.push(format!(
+                        "services.{}.{} is not supported and was dropped",
+                        name, key
+                    )),
+                },
+            }
+        }
+        if !cmd.is_empty() {
+            options.insert("cmd".to_string(), ComposeConverter::string_list(cmd));
+        }
+        if !extra_options.is_empty() {
+            options.insert(
+                "extraOptions".to_string(),
+                ComposeConverter::string_list(extra_options),
+            );
+        }
+        options
+    }
+}
+
+impl Converter for ComposeConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::yaml
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::nixos {
+            return None;
+        }
+        let mut warnings = vec![];
+        let mut containers = vec![];
+        for variable in variables {
+            match (variable.name.as_str(), &variable.value) {
+                ("services", NixVariableValue::AttributeSet(services)) => {
+                    // Containers are named after the service unless renamed
+                    let names = services
+                        .iter()
+                        .map(|(name, service)| match service {
+                            NixVariableValue::AttributeSet(service) => Some((
+                                name.to_owned(),
+                                service
+                                    .get("container_name")
+                                    .map(plain_string)
+                                    .unwrap_or(name.to_owned()),
+                            )),
+                            _ => None,
+                        })
+                        .collect::<Option<IndexMap<String, String>>>()?;
+                    for (name, service) in services {
+                        let NixVariableValue::AttributeSet(service) = service else {
+                            return None;
+                        };
+                        containers.push(NixVariable::new(
+                            &format!(
+                                "virtualisation.oci-containers.containers.{}",
+                                attr_name(&names[name])
+                            ),
+                            &NixVariableValue::AttributeSet(ComposeConverter::convert_service(
+                                name,
+                                service,
+                                &names,
+                                &mut warnings,
+                            )),
+                        ));
+                    }
+                }
+                ("networks", NixVariableValue::AttributeSet(networks)) => {
+                    // External networks are expected to exist already
+                    warnings.extend(
+                        networks
+                            .iter()
+                            .filter(|(_, network)| {
+                                !matches!(network, NixVariableValue::AttributeSet(a)
+                                    if a.get("external") == Some(&NixVariableValue::Boolean(true)))
+                            })
+                            .map(|(network, _)| {
+                                format!(
+                                    "networks.{} is not created, it has to exist before the containers using it start",
+                                    network
+                                )
+                            }),
+                    )
+                }
+                ("version" | "name", _) => {}
+                (key, _) => warnings.push(format!(
+                    "Top level key {} is not supported and was dropped",
+                    key
+                )),
+            }
+        }
+        Some(Conversion {
+            variables: containers,
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{compose::ComposeConverter, Converter, Target};
+    use crate::parser::{yaml::YamlParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_compose() {
+        let parsed = YamlParser::new().parse(COMPOSE).unwrap();
+        assert!(ComposeConverter::new()
+            .convert("compose", &parsed, &Target::home_manager)
+            .is_none());
+        let converted = ComposeConverter::new().convert("compose", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "services.web.build is not supported and was dropped",
+                "services.web.depends_on.db.condition (service_healthy) is not supported by dependsOn and was dropped",
+                "services.web.restart was dropped, containers are restarted by systemd",
+                "services.cache.volumes: the tmpfs at /data is not supported and was dropped",
+                "Top level key volumes is not supported and was dropped",
+                "networks.frontend is not created, it has to exist before the containers using it start",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const COMPOSE: &str = "
+version: '3.8'
+services:
+  web:
+    image: nginx:1.25
+    build: .
+    ports:
+      - 8080:80
+      - target: 443
+        published: 8443
+        protocol: tcp
+    volumes:
+      - ./html:/usr/share/nginx/html:ro
+    environment:
+      - TZ=Europe/Berlin
+    depends_on:
+      db:
+        condition: service_healthy
+    networks:
+      - frontend
+      - backend
+    restart: always
+  cache:
+    image: redis:7
+    volumes:
+      - type: tmpfs
+        target: /data
+  db:
+    image: postgres:16
+    container_name: database
+    command: postgres -c 'max_connections=200'
+    env_file: .env
+    environment:
+      POSTGRES_DB: app
+      PGPORT: 5432
+    healthcheck:
+      test: [\"CMD\", \"pg_isready\"]
+      interval: 10s
+volumes:
+  data:
+networks:
+  frontend:
+  backend:
+    external: true
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+virtualisation.oci-containers.containers.web = {
+image = \"nginx:1.25\";
+ports = [
+\"8080:80\"
+\"8443:443/tcp\"
+];
+volumes = [
+\"./html:/usr/share/nginx/html:ro\"
+];
+environment = {
+TZ = \"Europe/Berlin\";
+};
+dependsOn = [
+\"database\"
+];
+extraOptions = [
+\"--network=frontend\"
+\"--network=backend\"
+];
+};
+virtualisation.oci-containers.containers.cache = {
+image = \"redis:7\";
+};
+virtualisation.oci-containers.containers.database = {
+image = \"postgres:16\";
+environmentFiles = [
+\".env\"
+];
+environment = {
+POSTGRES_DB = \"app\";
+PGPORT = \"5432\";
+};
+cmd = [
+\"postgres\"
+\"-c\"
+\"max_connections=200\"
+];
+extraOptions = [
+\"--health-cmd=pg_isready\"
+\"--health-interval=10s\"
+];
+};
+}";
+}
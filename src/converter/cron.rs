@@ -0,0 +1,587 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const SYSTEMD_WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+/// Shell builtins commonly preceding the actual job, e.g. `cd /srv && ./backup.sh`.
+const BUILTINS: [&str; 10] = [
+    "cd", "test", "[", "[[", "true", ":", "sleep", "export", "umask", "source",
+];
+
+/// Converts crontabs into pairs of systemd timers and services, or into
+/// `services.cron.systemCronJobs` with [`CronConverter::with_system_cron_jobs`].
+///
+/// Jobs of crontabs without a user column run as the user given with
+/// [`CronConverter::with_user`]. NixOS needs a user for every job, so these
+/// crontabs can't be converted for it without one.
+pub struct CronConverter {
+    system_cron_jobs: bool,
+    user: Option<String>,
+}
+
+impl Default for CronConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CronConverter {
+    pub fn new() -> CronConverter {
+        CronConverter {
+            system_cron_jobs: false,
+            user: None,
+        }
+    }
+
+    /// The user running jobs without a user column.
+    pub fn with_user(mut self, user: &str) -> CronConverter {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    /// Keep the jobs as cron jobs instead of translating them into timers.
+    pub fn with_system_cron_jobs(mut self) -> CronConverter {
+        self.system_cron_jobs = true;
+        self
+    }
+
+    /// Parses a single value of a cron field, resolving month and day names.
+    fn parse_number(value: &str, min: u32, max: u32, names: &[&str]) -> Option<u32> {
+        let number = match names
+            .iter()
+            .position(|name| value.eq_ignore_ascii_case(name))
+        {
+            Some(index) => index as u32 + min,
+            None => value.parse().ok()?,
+        };
+        (min..=max).contains(&number).then_some(number)
+    }
+
+    /// Parses one entry of a comma separated cron field into its first and
+    /// last value and an optional step.
+    fn parse_range(
+        range: &str,
+        min: u32,
+        max: u32,
+        names: &[&str],
+    ) -> Option<(u32, u32, Option<u32>)> {
+        let (range, step) = match range.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u32>().ok().filter(|s| *s > 0)?)),
+            None => (range, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (
+                CronConverter::parse_number(start, min, max, names)?,
+                CronConverter::parse_number(end, min, max, names)?,
+            ),
+            // A single value with a step repeats until the maximum
+            None if step.is_some() => (CronConverter::parse_number(range, min, max, names)?, max),
+            None => {
+                let value = CronConverter::parse_number(range, min, max, names)?;
+                (value, value)
+            }
+        };
+        (start <= end).then_some((start, end, step))
+    }
+
+    /// Translates a cron field into the matching component of a calendar event.
+    fn translate_field(field: &str, min: u32, max: u32, names: &[&str]) -> Option<String> {
+        if field == "*" {
+            return Some("*".to_string());
+        }
+        let format = |n: u32| format!("{:02}", n);
+        field
+            .split(',')
+            .map(|range| {
+                let (start, end, step) = CronConverter::parse_range(range, min, max, names)?;
+                Some(match step {
+                    // Calendar events only repeat up to the maximum, so other
+                    // stepped ranges have to be expanded
+                    Some(step) if end == max => format!("{}/{}", format(start), step),
+                    Some(step) => (start..=end)
+                        .step_by(step as usize)
+                        .map(format)
+                        .collect::<Vec<String>>()
+                        .join(","),
+                    None if start == end => format(start),
+                    None => format!("{}..{}", format(start), format(end)),
+                })
+            })
+            .collect::<Option<Vec<String>>>()
+            .map(|parts| parts.join(","))
+    }
+
+    /// Translates the weekday field. Calendar events start the week on
+    /// monday and don't support steps, so the days are expanded and runs of
+    /// consecutive days are merged into ranges again.
+    fn translate_weekdays(field: &str) -> Option<String> {
+        if field == "*" {
+            return Some("*".to_string());
+        }
+        let mut days = [false; 7];
+        for range in field.split(',') {
+            // Cron accepts both 0 and 7 for sunday
+            let (start, end, step) = CronConverter::parse_range(range, 0, 7, &WEEKDAYS)?;
+            for day in (start..=end).step_by(step.unwrap_or(1) as usize) {
+                days[day as usize % 7] = true;
+            }
+        }
+        let mut parts: Vec<String> = vec![];
+        let mut run: Vec<&str> = vec![];
+        for day in [1, 2, 3, 4, 5, 6, 0, 7] {
+            if day < 7 && days[day] {
+                run.push(SYSTEMD_WEEKDAYS[day]);
+            } else if run.len() > 2 {
+                parts.push(format!("{}..{}", run[0], run[run.len() - 1]));
+                run.clear();
+            } else {
+                parts.extend(run.drain(..).map(str::to_string));
+            }
+        }
+        Some(parts.join(","))
+    }
+
+    /// Translates a cron schedule into a systemd calendar event. Returns the
+    /// event and whether it matches exactly the same times.
+    pub fn translate_schedule(schedule: &str) -> Option<(String, bool)> {
+        match schedule {
+            "@yearly" | "@annually" => return Some(("yearly".to_string(), true)),
+            "@monthly" => return Some(("monthly".to_string(), true)),
+            "@weekly" => return Some(("Sun *-*-* 00:00:00".to_string(), true)),
+            "@daily" | "@midnight" => return Some(("daily".to_string(), true)),
+            "@hourly" => return Some(("hourly".to_string(), true)),
+            _ => {}
+        }
+        let fields: Vec<&str> = schedule.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return None;
+        };
+        let minute = CronConverter::translate_field(minute, 0, 59, &[])?;
+        let hour = CronConverter::translate_field(hour, 0, 23, &[])?;
+        let day = CronConverter::translate_field(day, 1, 31, &[])?;
+        let month = CronConverter::translate_field(month, 1, 12, &MONTHS)?;
+        let weekday = CronConverter::translate_weekdays(weekday)?;
+        // Cron runs a job if either the day of month or the weekday matches,
+        // calendar events require both to match
+        let exact = day == "*" || weekday == "*";
+        let date = format!("*-{}-{} {}:{}:00", month, day, hour, minute);
+        Some(if weekday == "*" {
+            (date, exact)
+        } else {
+            (format!("{} {}", weekday, date), exact)
+        })
+    }
+
+    /// Derives a unit name from the executable of a command, skipping
+    /// variable assignments and shell builtins. Commands without one are
+    /// numbered instead.
+    fn unit_name(command: &str, names: &[String]) -> String {
+        let executable = command
+            .replace("&&", ";")
+            .replace("||", ";")
+            .replace('|', ";")
+            .split(';')
+            .filter_map(|command| {
+                command
+                    .split_whitespace()
+                    .find(|word| !word.contains('='))
+                    .map(str::to_string)
+            })
+            .find(|executable| !BUILTINS.contains(&executable.as_str()))
+            .unwrap_or(format!("job-{}", names.len() + 1));
+        let executable = executable.rsplit('/').next().unwrap_or(&executable);
+        let name: String = executable
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let name = format!("cron-{}", name.trim_matches('-'));
+        let mut unique = name.to_owned();
+        let mut count = 1;
+        while names.contains(&unique) {
+            count += 1;
+            unique = format!("{}-{}", name, count);
+        }
+        unique
+    }
+
+    /// The user a job runs as, its own or the one given to the converter.
+    fn user(&self, job: &IndexMap<String, NixVariableValue>) -> Option<String> {
+        job.get("user").map(plain_string).or(self.user.to_owned())
+    }
+
+    fn convert_cron_jobs(
+        &self,
+        environment: &IndexMap<String, NixVariableValue>,
+        jobs: &[NixVariableValue],
+    ) -> Option<Vec<NixVariable>> {
+        let lines = environment
+            .iter()
+            .map(|(variable, value)| Some(format!("{}={}", variable, plain_string(value))))
+            .chain(jobs.iter().map(|job| {
+                let NixVariableValue::AttributeSet(job) = job else {
+                    return None;
+                };
+                Some(format!(
+                    "{} {} {}",
+                    plain_string(job.get("schedule")?),
+                    self.user(job)?,
+                    plain_string(job.get("command")?)
+                ))
+            }))
+            .map(|line| line.map(NixVariableValue::String))
+            .collect::<Option<Vec<NixVariableValue>>>()?;
+        Some(vec![
+            NixVariable::new("services.cron.enable", &NixVariableValue::Boolean(true)),
+            NixVariable::new(
+                "services.cron.systemCronJobs",
+                &NixVariableValue::List(lines),
+            ),
+        ])
+    }
+}
+
+impl Converter for CronConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::crontab
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        let mut environment = IndexMap::new();
+        let mut jobs = vec![];
+        for variable in variables {
+            match (variable.name.as_str(), &variable.value) {
+                ("environment", NixVariableValue::AttributeSet(a)) => environment = a.to_owned(),
+                ("jobs", NixVariableValue::List(l)) => jobs = l.to_owned(),
+                _ => return None,
+            }
+        }
+        if self.system_cron_jobs {
+            return match target {
+                Target::nixos => Some(Conversion::new(
+                    self.convert_cron_jobs(&environment, &jobs)?,
+                )),
+                Target::home_manager => None,
+            };
+        }
+
+        let mut warnings = vec![];
+        for variable in ["MAILTO", "PATH", "SHELL"] {
+            if environment.shift_remove(variable).is_some() {
+                warnings.push(format!(
+                    "{} has no equivalent for timers and was dropped",
+                    variable
+                ));
+            }
+        }
+        let mut names = vec![];
+        let mut variables = vec![];
+        for job in jobs {
+            let NixVariableValue::AttributeSet(job) = job else {
+                return None;
+            };
+            let schedule = plain_string(job.get("schedule")?);
+            let command = plain_string(job.get("command")?);
+            let unit = CronConverter::unit_name(&command, &names);
+            names.push(unit.to_owned());
+
+            let timer = match schedule.as_str() {
+                "@reboot" => ("OnBootSec", "0".to_string()),
+                schedule => match CronConverter::translate_schedule(schedule) {
+                    Some((calendar, exact)) => {
+                        if !exact {
+                            warnings.push(format!(
+                                "{}: \"{}\" runs when either the day of month or the weekday match, {} requires both",
+                                unit, schedule, calendar
+                            ));
+                        }
+                        ("OnCalendar", calendar)
+                    }
+                    None => {
+                        warnings.push(format!(
+                            "{}: the schedule \"{}\" could not be translated and was dropped",
+                            unit, schedule
+                        ));
+                        continue;
+                    }
+                },
+            };
+            if command.replace("\\%", "").contains('%') {
+                warnings.push(format!(
+                    "{}: % is not treated as a newline by systemd services",
+                    unit
+                ));
+            }
+            let command = command.replace("\\%", "%");
+            let user = self.user(&job);
+
+            let (prefix, service, timer) = match target {
+                Target::nixos => {
+                    let mut service = IndexMap::from([
+                        (
+                            "description".to_string(),
+                            NixVariableValue::String(command.to_owned()),
+                        ),
+                        ("script".to_string(), NixVariableValue::String(command)),
+                    ]);
+                    if !environment.is_empty() {
+                        service.insert(
+                            "environment".to_string(),
+                            NixVariableValue::AttributeSet(environment.to_owned()),
+                        );
+                    }
+                    let mut service_config = IndexMap::from([(
+                        "Type".to_string(),
+                        NixVariableValue::String("oneshot".to_string()),
+                    )]);
+                    service_config.insert("User".to_string(), NixVariableValue::String(user?));
+                    service.insert(
+                        "serviceConfig".to_string(),
+                        NixVariableValue::AttributeSet(service_config),
+                    );
+                    let timer = IndexMap::from([
+                        (
+                            "wantedBy".to_string(),
+                            NixVariableValue::List(vec![NixVariableValue::String(
+                                "timers.target".to_string(),
+                            )]),
+                        ),
+                        (
+                            "timerConfig".to_string(),
+                            NixVariableValue::AttributeSet(IndexMap::from([(
+                                timer.0.to_string(),
+                                NixVariableValue::String(timer.1),
+                            )])),
+                        ),
+                    ]);
+                    ("systemd", service, timer)
+                }
+                Target::home_manager => {
+                    let description = NixVariableValue::AttributeSet(IndexMap::from([(
+                        "Description".to_string(),
+                        NixVariableValue::String(command.to_owned()),
+                    )]));
+                    let mut service_config = IndexMap::from([
+                        (
+                            "Type".to_string(),
+                            NixVariableValue::String("oneshot".to_string()),
+                        ),
+                        (
+                            "ExecStart".to_string(),
+                            NixVariableValue::Function(
+                                "toString".to_string(),
+                                vec![NixVariableValue::Function(
+                                    "pkgs.writeShellScript".to_string(),
+                                    vec![
+                                        NixVariableValue::String(unit.to_owned()),
+                                        NixVariableValue::String(command),
+                                    ],
+                                )],
+                            ),
+                        ),
+                    ]);
+                    if !environment.is_empty() {
+                        service_config.insert(
+                            "Environment".to_string(),
+                            NixVariableValue::List(
+                                environment
+                                    .iter()
+                                    .map(|(variable, value)| {
+                                        NixVariableValue::String(format!(
+                                            "{}={}",
+                                            variable,
+                                            plain_string(value)
+                                        ))
+                                    })
+                                    .collect(),
+                            ),
+                        );
+                    }
+                    let service = IndexMap::from([
+                        ("Unit".to_string(), description.to_owned()),
+                        (
+                            "Service".to_string(),
+                            NixVariableValue::AttributeSet(service_config),
+                        ),
+                    ]);
+                    let timer = IndexMap::from([
+                        ("Unit".to_string(), description),
+                        (
+                            "Timer".to_string(),
+                            NixVariableValue::AttributeSet(IndexMap::from([(
+                                timer.0.to_string(),
+                                NixVariableValue::String(timer.1),
+                            )])),
+                        ),
+                        (
+                            "Install".to_string(),
+                            NixVariableValue::AttributeSet(IndexMap::from([(
+                                "WantedBy".to_string(),
+                                NixVariableValue::List(vec![NixVariableValue::String(
+                                    "timers.target".to_string(),
+                                )]),
+                            )])),
+                        ),
+                    ]);
+                    ("systemd.user", service, timer)
+                }
+            };
+            variables.push(NixVariable::new(
+                &format!("{}.services.{}", prefix, attr_name(&unit)),
+                &NixVariableValue::AttributeSet(service),
+            ));
+            variables.push(NixVariable::new(
+                &format!("{}.timers.{}", prefix, attr_name(&unit)),
+                &NixVariableValue::AttributeSet(timer),
+            ));
+        }
+        Some(Conversion {
+            variables,
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{cron::CronConverter, Converter, Target};
+    use crate::parser::{crontab::CrontabParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_translate_schedule() {
+        let translate = |schedule| CronConverter::translate_schedule(schedule).unwrap();
+        assert_eq!(
+            translate("@weekly"),
+            ("Sun *-*-* 00:00:00".to_string(), true)
+        );
+        assert_eq!(
+            translate("*/15 * * * *"),
+            ("*-*-* *:00/15:00".to_string(), true)
+        );
+        assert_eq!(
+            translate("30 2 1-10/3 jan-mar,dec *"),
+            ("*-01..03,12-01,04,07,10 02:30:00".to_string(), true)
+        );
+        assert_eq!(
+            translate("0 9 * * 1-5"),
+            ("Mon..Fri *-*-* 09:00:00".to_string(), true)
+        );
+        assert_eq!(
+            translate("0 0 1 * */2"),
+            ("Tue,Thu,Sat,Sun *-*-01 00:00:00".to_string(), false)
+        );
+        assert_eq!(
+            translate("0 0 * * 5-7"),
+            ("Fri..Sun *-*-* 00:00:00".to_string(), true)
+        );
+        assert!(CronConverter::translate_schedule("61 * * * *").is_none());
+    }
+
+    #[test]
+    fn test_unit_name() {
+        let names = vec!["cron-backup-sh".to_string()];
+        assert_eq!(
+            CronConverter::unit_name("cd / && ./backup.sh", &[]),
+            "cron-backup-sh"
+        );
+        assert_eq!(
+            CronConverter::unit_name("LANG=C sleep 30; /usr/bin/backup.sh >/dev/null", &names),
+            "cron-backup-sh-2"
+        );
+        assert_eq!(CronConverter::unit_name("cd /tmp", &names), "cron-job-2");
+    }
+
+    #[test]
+    fn test_cron_nixos() {
+        let parsed = CrontabParser::new().parse(CRONTAB).unwrap();
+        // The file name is no user
+        assert!(CronConverter::new()
+            .convert("alice", &parsed, &Target::nixos)
+            .is_none());
+        let converted = CronConverter::new().with_user("alice").convert(
+            "mycrontab.txt",
+            &parsed,
+            &Target::nixos,
+        );
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec!["MAILTO has no equivalent for timers and was dropped"]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    #[test]
+    fn test_cron_jobs() {
+        let parsed = CrontabParser::new().parse(CRONTAB).unwrap();
+        let converted = CronConverter::new()
+            .with_system_cron_jobs()
+            .with_user("alice")
+            .convert("mycrontab.txt", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(
+            generated.unwrap(),
+            "{ config, pkgs, ... }:
+{
+services.cron.enable = true;
+services.cron.systemCronJobs = [
+\"MAILTO=admin@example.com\"
+\"BACKUP_DIR=/srv/backup\"
+\"*/15 9-17 * * mon-fri alice /usr/bin/sync-mail --quiet\"
+];
+}"
+        );
+    }
+
+    const CRONTAB: &str = "
+MAILTO=admin@example.com
+BACKUP_DIR=/srv/backup
+*/15 9-17 * * mon-fri /usr/bin/sync-mail --quiet
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+systemd.services.cron-sync-mail = {
+description = \"/usr/bin/sync-mail --quiet\";
+script = \"/usr/bin/sync-mail --quiet\";
+environment = {
+BACKUP_DIR = \"/srv/backup\";
+};
+serviceConfig = {
+Type = \"oneshot\";
+User = \"alice\";
+};
+};
+systemd.timers.cron-sync-mail = {
+wantedBy = [
+\"timers.target\"
+];
+timerConfig = {
+OnCalendar = \"Mon..Fri *-*-* 09..17:00/15:00\";
+};
+};
+}";
+}
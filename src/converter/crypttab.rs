@@ -0,0 +1,160 @@
+use super::{fstab::device_path, plain_string, rows, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Converts `/etc/crypttab` into `boot.initrd.luks.devices`.
+///
+/// Options without a dedicated option are kept in `crypttabExtraOpts`, which
+/// only the systemd based initrd understands.
+pub struct CrypttabConverter {}
+
+impl Default for CrypttabConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrypttabConverter {
+    pub fn new() -> CrypttabConverter {
+        CrypttabConverter {}
+    }
+
+    fn number(value: &str) -> Option<NixVariableValue> {
+        value.parse().ok().map(NixVariableValue::Number)
+    }
+}
+
+impl Converter for CrypttabConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::crypttab
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if !matches!(target, Target::nixos) {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![]);
+        for row in rows(variables)? {
+            let field = |column: &str| row.get(column).map(plain_string);
+            let name = field("name")?;
+            let key_file =
+                field("password").filter(|password| !["none", "-"].contains(&password.as_str()));
+            if key_file.as_ref().is_some_and(|key_file| {
+                key_file.starts_with("/dev/urandom") || key_file.starts_with("/dev/random")
+            }) {
+                conversion.warnings.push(format!(
+                    "{} uses a random key, use swapDevices.*.randomEncryption instead",
+                    name
+                ));
+                continue;
+            }
+
+            let mut device = IndexMap::from([(
+                "device".to_string(),
+                NixVariableValue::String(device_path(&field("device")?)),
+            )]);
+            if let Some(key_file) = key_file {
+                device.insert("keyFile".to_string(), NixVariableValue::String(key_file));
+            }
+            let mut extra_options = vec![];
+            for option in field("options").unwrap_or_default().split(',') {
+                let (key, value) = match option.split_once('=') {
+                    Some((key, value)) => (key, Some(value)),
+                    None => (option, None),
+                };
+                let mapped = match (key, value) {
+                    ("" | "luks", None) => continue,
+                    ("discard", None) => Some(("allowDiscards", NixVariableValue::Boolean(true))),
+                    ("no-read-workqueue" | "no-write-workqueue", None) => {
+                        Some(("bypassWorkqueues", NixVariableValue::Boolean(true)))
+                    }
+                    ("keyfile-offset", Some(value)) => {
+                        CrypttabConverter::number(value).map(|offset| ("keyFileOffset", offset))
+                    }
+                    ("keyfile-size", Some(value)) => {
+                        CrypttabConverter::number(value).map(|size| ("keyFileSize", size))
+                    }
+                    ("header", Some(value)) => {
+                        Some(("header", NixVariableValue::String(value.to_string())))
+                    }
+                    _ => None,
+                };
+                match mapped {
+                    Some((option, value)) => {
+                        device.insert(option.to_string(), value);
+                    }
+                    None => extra_options.push(NixVariableValue::String(option.to_string())),
+                }
+            }
+            if !extra_options.is_empty() {
+                conversion.warnings.push(format!(
+                    "{}: crypttabExtraOpts are only used by the systemd initrd",
+                    name
+                ));
+                device.insert(
+                    "crypttabExtraOpts".to_string(),
+                    NixVariableValue::List(extra_options),
+                );
+            }
+            conversion.variables.push(NixVariable::new(
+                &format!("boot.initrd.luks.devices.{}", attr_name(&name)),
+                &NixVariableValue::AttributeSet(device),
+            ));
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{crypttab::CrypttabConverter, Converter, Target};
+    use crate::parser::{columns::ColumnParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_crypttab() {
+        let parser = ColumnParser::new(&["name", "device", "password", "options"], 2);
+        let parsed = parser.parse(CRYPTTAB).unwrap();
+        let converted = CrypttabConverter::new().convert("crypttab", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "data: crypttabExtraOpts are only used by the systemd initrd",
+                "cryptswap uses a random key, use swapDevices.*.randomEncryption instead",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const CRYPTTAB: &str = "
+# <name> <device> <password> <options>
+cryptroot UUID=6f1e4e27-3b1d-4c39-9d0b-0d3e0a6b1c55 none luks,discard
+data /dev/sdb2 /etc/keys/data.key luks,keyfile-offset=512,tries=3
+cryptswap /dev/sda3 /dev/urandom swap,cipher=aes-xts-plain64
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+boot.initrd.luks.devices.cryptroot = {
+device = \"/dev/disk/by-uuid/6f1e4e27-3b1d-4c39-9d0b-0d3e0a6b1c55\";
+allowDiscards = true;
+};
+boot.initrd.luks.devices.data = {
+device = \"/dev/sdb2\";
+keyFile = \"/etc/keys/data.key\";
+keyFileOffset = 512;
+crypttabExtraOpts = [
+\"tries=3\"
+];
+};
+}";
+}
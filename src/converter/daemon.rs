@@ -0,0 +1,389 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Describes how the keywords of a daemon configuration map onto its NixOS
+/// module. Keywords are matched case-insensitively.
+pub struct Profile {
+    format: SupportedFormats,
+    module: &'static str,
+    /// Keywords with a dedicated list option. Values are wrapped into an
+    /// attribute set if an attribute name is given.
+    options: &'static [(&'static str, &'static str, Option<&'static str>)],
+    /// Keywords taking a list of words or comma separated values.
+    lists: &'static [&'static str],
+    /// Keywords taking `yes` or `no` among other strings, so they must not
+    /// become booleans.
+    strings: &'static [&'static str],
+    /// Keywords the module sets by itself and the option to use instead.
+    managed: &'static [(&'static str, &'static str)],
+    /// Other known keywords, in the casing of the module's `settings`.
+    keywords: &'static [&'static str],
+}
+
+/// The OpenSSH daemon, mapped onto `services.openssh`.
+pub const SSHD: Profile = Profile {
+    format: SupportedFormats::sshd_config,
+    module: "services.openssh",
+    options: &[
+        ("Port", "ports", None),
+        ("ListenAddress", "listenAddresses", Some("addr")),
+        ("AuthorizedKeysFile", "authorizedKeysFiles", None),
+    ],
+    lists: &[
+        "AllowUsers",
+        "AllowGroups",
+        "DenyUsers",
+        "DenyGroups",
+        "AcceptEnv",
+        "Ciphers",
+        "Macs",
+        "KexAlgorithms",
+    ],
+    strings: &["PermitRootLogin", "GatewayPorts"],
+    managed: &[
+        ("HostKey", "services.openssh.hostKeys"),
+        ("Subsystem", "services.openssh.sftpServerExecutable"),
+    ],
+    keywords: &[
+        "AddressFamily",
+        "AllowAgentForwarding",
+        "AllowStreamLocalForwarding",
+        "AllowTcpForwarding",
+        "AuthenticationMethods",
+        "AuthorizedKeysCommand",
+        "AuthorizedKeysCommandUser",
+        "AuthorizedPrincipalsCommand",
+        "AuthorizedPrincipalsCommandUser",
+        "AuthorizedPrincipalsFile",
+        "Banner",
+        "CASignatureAlgorithms",
+        "ChallengeResponseAuthentication",
+        "ChannelTimeout",
+        "ChrootDirectory",
+        "ClientAliveCountMax",
+        "ClientAliveInterval",
+        "Compression",
+        "DisableForwarding",
+        "ExposeAuthInfo",
+        "FingerprintHash",
+        "ForceCommand",
+        "GSSAPIAuthentication",
+        "GSSAPICleanupCredentials",
+        "GSSAPIStrictAcceptorCheck",
+        "HostbasedAcceptedAlgorithms",
+        "HostbasedAuthentication",
+        "HostbasedUsesNameFromPacketOnly",
+        "HostCertificate",
+        "HostKeyAgent",
+        "HostKeyAlgorithms",
+        "IgnoreRhosts",
+        "IgnoreUserKnownHosts",
+        "IPQoS",
+        "KbdInteractiveAuthentication",
+        "KerberosAuthentication",
+        "KerberosGetAFSToken",
+        "KerberosOrLocalPasswd",
+        "KerberosTicketCleanup",
+        "LoginGraceTime",
+        "LogLevel",
+        "LogVerbose",
+        "MaxAuthTries",
+        "MaxSessions",
+        "MaxStartups",
+        "ModuliFile",
+        "PasswordAuthentication",
+        "PermitEmptyPasswords",
+        "PermitListen",
+        "PermitOpen",
+        "PermitTTY",
+        "PermitTunnel",
+        "PermitUserEnvironment",
+        "PermitUserRC",
+        "PerSourceMaxStartups",
+        "PerSourceNetBlockSize",
+        "PidFile",
+        "PrintLastLog",
+        "PrintMotd",
+        "PubkeyAcceptedAlgorithms",
+        "PubkeyAuthOptions",
+        "PubkeyAuthentication",
+        "RekeyLimit",
+        "RequiredRSASize",
+        "RevokedKeys",
+        "SecurityKeyProvider",
+        "SetEnv",
+        "StreamLocalBindMask",
+        "StreamLocalBindUnlink",
+        "StrictModes",
+        "SyslogFacility",
+        "TCPKeepAlive",
+        "TrustedUserCAKeys",
+        "UnusedConnectionTimeout",
+        "UseDns",
+        "UsePAM",
+        "VersionAddendum",
+        "X11DisplayOffset",
+        "X11Forwarding",
+        "X11UseLocalhost",
+        "XAuthLocation",
+    ],
+};
+
+/// Converts `Keyword value` daemon configurations into the `settings` of a
+/// NixOS module, as described by a [`Profile`].
+///
+/// `yes` and `no` become booleans, numbers become integers and repeated
+/// keywords become lists. Known keywords are written in the casing of the
+/// module, unknown ones are reported. `Match` blocks have no structured equivalent and are
+/// kept in `extraConfig`.
+pub struct DaemonConverter {
+    profile: &'static Profile,
+}
+
+impl DaemonConverter {
+    pub fn new(profile: &'static Profile) -> DaemonConverter {
+        DaemonConverter { profile }
+    }
+
+    fn find<'a>(keywords: impl IntoIterator<Item = &'a str>, keyword: &str) -> Option<&'a str> {
+        keywords
+            .into_iter()
+            .find(|k| k.eq_ignore_ascii_case(keyword))
+    }
+
+    /// All values of a possibly repeated keyword.
+    fn values(value: &NixVariableValue) -> Vec<String> {
+        match value {
+            NixVariableValue::List(l) => l.iter().map(plain_string).collect(),
+            value => vec![plain_string(value)],
+        }
+    }
+
+    fn typed(value: &str) -> NixVariableValue {
+        match value {
+            "yes" => NixVariableValue::Boolean(true),
+            "no" => NixVariableValue::Boolean(false),
+            value => match value.parse::<i64>() {
+                Ok(i) => NixVariableValue::Number(i as f64),
+                Err(_) => NixVariableValue::String(value.to_string()),
+            },
+        }
+    }
+
+    /// Converts a keyword and its values into a setting. Keywords are written
+    /// in the casing of the module, as nix attribute names are case-sensitive.
+    fn setting(
+        &self,
+        keyword: &str,
+        values: Vec<String>,
+        warnings: &mut Vec<String>,
+    ) -> (String, NixVariableValue) {
+        if let Some(keyword) = DaemonConverter::find(self.profile.lists.iter().copied(), keyword) {
+            let words = values
+                .iter()
+                .flat_map(|value| value.split(|c: char| c.is_whitespace() || c == ','))
+                .filter(|word| !word.is_empty())
+                .map(|word| NixVariableValue::String(word.to_string()))
+                .collect();
+            return (keyword.to_string(), NixVariableValue::List(words));
+        }
+        let (keyword, convert): (String, fn(&str) -> NixVariableValue) = match DaemonConverter::find(
+            self.profile.strings.iter().copied(),
+            keyword,
+        ) {
+            Some(keyword) => (keyword.to_string(), |v| {
+                NixVariableValue::String(v.to_string())
+            }),
+            None => match DaemonConverter::find(self.profile.keywords.iter().copied(), keyword) {
+                Some(keyword) => (keyword.to_string(), DaemonConverter::typed),
+                None => {
+                    warnings.push(format!(
+                            "{} is no known keyword and was kept as written, check its casing as settings are case-sensitive",
+                            keyword
+                        ));
+                    (keyword.to_string(), DaemonConverter::typed)
+                }
+            },
+        };
+        let mut values: Vec<NixVariableValue> = values.iter().map(|v| convert(v)).collect();
+        let value = match values.len() {
+            1 => values.remove(0),
+            _ => NixVariableValue::List(values),
+        };
+        (keyword, value)
+    }
+
+    fn block(header: &str, values: &IndexMap<String, NixVariableValue>) -> String {
+        let mut lines = vec![header.to_string()];
+        for (keyword, value) in values {
+            for value in DaemonConverter::values(value) {
+                lines.push(format!("  {} {}", keyword, value));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Converter for DaemonConverter {
+    fn format(&self) -> SupportedFormats {
+        self.profile.format
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if !matches!(target, Target::nixos) {
+            return None;
+        }
+        let module = self.profile.module;
+        let mut conversion = Conversion::new(vec![NixVariable::new(
+            &format!("{}.enable", module),
+            &NixVariableValue::Boolean(true),
+        )]);
+        let mut settings = IndexMap::new();
+        let mut blocks = vec![];
+        for variable in variables {
+            if let NixVariableValue::AttributeSet(values) = &variable.value {
+                blocks.push(DaemonConverter::block(&variable.name, values));
+                continue;
+            }
+            let values = DaemonConverter::values(&variable.value);
+            if let Some((_, option, attribute)) = self
+                .profile
+                .options
+                .iter()
+                .find(|(k, _, _)| k.eq_ignore_ascii_case(&variable.name))
+            {
+                let list = values
+                    .iter()
+                    .flat_map(|value| value.split_whitespace())
+                    .map(|value| match attribute {
+                        Some(attribute) => NixVariableValue::AttributeSet(IndexMap::from([(
+                            attribute.to_string(),
+                            NixVariableValue::String(value.to_string()),
+                        )])),
+                        None => DaemonConverter::typed(value),
+                    })
+                    .collect();
+                conversion.variables.push(NixVariable::new(
+                    &format!("{}.{}", module, option),
+                    &NixVariableValue::List(list),
+                ));
+            } else if let Some((keyword, option)) = self
+                .profile
+                .managed
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(&variable.name))
+            {
+                conversion.warnings.push(format!(
+                    "{} is set by the NixOS module and was dropped, use {} instead",
+                    keyword, option
+                ));
+            } else {
+                let (keyword, value) =
+                    self.setting(&variable.name, values, &mut conversion.warnings);
+                settings.insert(keyword, value);
+            }
+        }
+        if !settings.is_empty() {
+            conversion.variables.push(NixVariable::new(
+                &format!("{}.settings", module),
+                &NixVariableValue::AttributeSet(settings),
+            ));
+        }
+        if !blocks.is_empty() {
+            conversion.variables.push(NixVariable::new(
+                &format!("{}.extraConfig", module),
+                &NixVariableValue::String(blocks.join("\n")),
+            ));
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{
+        daemon::{DaemonConverter, SSHD},
+        Converter, Target,
+    };
+    use crate::parser::{keyword::KeywordValueParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_sshd() {
+        let parsed = KeywordValueParser::new()
+            .with_block_keywords(&["Match"])
+            .parse(SSHD_CONFIG)
+            .unwrap();
+        let converter = DaemonConverter::new(&SSHD);
+        assert!(converter
+            .convert("sshd_config", &parsed, &Target::home_manager)
+            .is_none());
+        let converted = converter.convert("sshd_config", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "HostKey is set by the NixOS module and was dropped, use services.openssh.hostKeys instead",
+                "MadeUpOption is no known keyword and was kept as written, check its casing as settings are case-sensitive",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const SSHD_CONFIG: &str = "
+Port 22
+Port 2222
+ListenAddress 0.0.0.0
+HostKey /etc/ssh/ssh_host_ed25519_key
+PermitRootLogin no
+passwordauthentication no
+X11Forwarding yes
+maxauthtries 3
+MadeUpOption yes
+AllowUsers alice bob
+KexAlgorithms curve25519-sha256,diffie-hellman-group16-sha512
+
+Match User backup
+    ForceCommand internal-sftp
+    ChrootDirectory /srv/backup
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+services.openssh.enable = true;
+services.openssh.ports = [
+22
+2222
+];
+services.openssh.listenAddresses = [
+{
+addr = \"0.0.0.0\";
+}
+];
+services.openssh.settings = {
+PermitRootLogin = \"no\";
+PasswordAuthentication = false;
+X11Forwarding = true;
+MaxAuthTries = 3;
+MadeUpOption = true;
+AllowUsers = [
+\"alice\"
+\"bob\"
+];
+KexAlgorithms = [
+\"curve25519-sha256\"
+\"diffie-hellman-group16-sha512\"
+];
+};
+services.openssh.extraConfig = \"Match User backup\\n  ForceCommand internal-sftp\\n  ChrootDirectory /srv/backup\";
+}";
+}
@@ -0,0 +1,170 @@
+use super::{Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Preferences Firefox keeps updating by itself, like timestamps, build
+/// information and telemetry IDs. `*` matches any number of characters.
+const VOLATILE_PREFS: [&str; 22] = [
+    "app.update.*",
+    "app.normandy.*",
+    "browser.laterrun.*",
+    "browser.migration.version",
+    "browser.newtabpage.activity-stream.impressionId",
+    "browser.sessionstore.upgradeBackup.*",
+    "browser.startup.homepage_override.*",
+    "datareporting.policy.dataSubmissionPolicyNotifiedTime",
+    "datareporting.sessions.*",
+    "distribution.*",
+    "extensions.last*",
+    "extensions.webextensions.uuids",
+    "gecko.*",
+    "idle.lastDailyNotification",
+    "places.database.lastMaintenance",
+    "storage.vacuum.last.*",
+    "toolkit.startup.last_success",
+    "toolkit.telemetry.cachedClientID",
+    "toolkit.telemetry.previousBuildID",
+    "toolkit.telemetry.reportingpolicy.firstRun",
+    "*lastupdate*",
+    "*timestamp*",
+];
+
+/// Whether a preference name matches a pattern of [`VOLATILE_PREFS`],
+/// ignoring case.
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// Converts Firefox preferences (`prefs.js` or `user.js`) into the settings
+/// of a home-manager profile or the NixOS `programs.firefox.preferences`.
+///
+/// The profile is named after the given name, file names like `prefs.js` fall
+/// back to `default`.
+pub struct FirefoxConverter {
+    drop_volatile: bool,
+}
+
+impl Default for FirefoxConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FirefoxConverter {
+    pub fn new() -> FirefoxConverter {
+        FirefoxConverter {
+            drop_volatile: false,
+        }
+    }
+
+    /// Drop preferences Firefox keeps updating by itself, see
+    /// [`VOLATILE_PREFS`].
+    pub fn with_volatile_prefs_dropped(mut self) -> FirefoxConverter {
+        self.drop_volatile = true;
+        self
+    }
+
+    fn volatile(name: &str) -> bool {
+        VOLATILE_PREFS.iter().any(|pattern| matches(pattern, name))
+    }
+}
+
+impl Converter for FirefoxConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::firefox_prefs
+    }
+
+    fn convert(
+        &self,
+        name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        let settings: IndexMap<String, NixVariableValue> = variables
+            .iter()
+            .filter(|pref| !self.drop_volatile || !FirefoxConverter::volatile(&pref.name))
+            .map(|pref| (pref.name.to_owned(), pref.value.to_owned()))
+            .collect();
+        let option = match target {
+            Target::home_manager => {
+                let profile = match name {
+                    "" | "prefs.js" | "user.js" => "default",
+                    name => name,
+                };
+                format!("programs.firefox.profiles.{}.settings", attr_name(profile))
+            }
+            Target::nixos => "programs.firefox.preferences".to_string(),
+        };
+        Some(Conversion::new(vec![
+            NixVariable::new("programs.firefox.enable", &NixVariableValue::Boolean(true)),
+            NixVariable::new(&option, &NixVariableValue::AttributeSet(settings)),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{
+        firefox::{matches, FirefoxConverter},
+        Converter, Target,
+    };
+    use crate::parser::{prefs::PrefsParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_volatile_patterns() {
+        assert!(matches("app.update.*", "app.update.lastUpdateTime.addon"));
+        assert!(matches("*lastupdate*", "media.gmp-manager.lastUpdate"));
+        assert!(matches("extensions.last*", "extensions.lastAppVersion"));
+        assert!(!matches("app.update.*", "app.updates"));
+        assert!(!matches("gecko.*", "browser.gecko.mstone"));
+    }
+
+    #[test]
+    fn test_firefox() {
+        let parsed = PrefsParser::new().parse(PREFS).unwrap();
+        let converted = FirefoxConverter::new()
+            .with_volatile_prefs_dropped()
+            .convert("prefs.js", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const PREFS: &str = "
+user_pref(\"app.update.lastUpdateTime.background-update-timer\", 1700000000);
+user_pref(\"browser.startup.homepage\", \"https://nixos.org\");
+user_pref(\"browser.startup.page\", 3);
+user_pref(\"extensions.lastAppVersion\", \"120.0\");
+user_pref(\"toolkit.telemetry.cachedClientID\", \"0d3e4f5a-1b2c-4d5e-8f90-a1b2c3d4e5f6\");
+user_pref(\"signon.rememberSignons\", false);
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+programs.firefox.enable = true;
+programs.firefox.profiles.default.settings = {
+\"browser.startup.homepage\" = \"https://nixos.org\";
+\"browser.startup.page\" = 3;
+\"signon.rememberSignons\" = false;
+};
+}";
+}
@@ -0,0 +1,259 @@
+use super::{plain_string, rows, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Device tags and the `/dev/disk` directory they resolve to.
+const DEVICE_TAGS: [(&str, &str); 4] = [
+    ("UUID=", "by-uuid"),
+    ("LABEL=", "by-label"),
+    ("PARTUUID=", "by-partuuid"),
+    ("PARTLABEL=", "by-partlabel"),
+];
+
+/// Pseudo file systems NixOS mounts by itself.
+const MANAGED_MOUNTS: [&str; 6] = ["/proc", "/sys", "/dev", "/dev/pts", "/dev/shm", "/run"];
+
+/// File system types NixOS never checks, so a pass of 0 needs no `noCheck`.
+const UNCHECKED_TYPES: [&str; 12] = [
+    "none", "auto", "btrfs", "zfs", "tmpfs", "nfs", "nfs4", "cifs", "squashfs", "9p", "vfat",
+    "swap",
+];
+
+/// Resolves `UUID=`, `LABEL=`, `PARTUUID=` and `PARTLABEL=` device specs to
+/// their `/dev/disk` path.
+pub(crate) fn device_path(device: &str) -> String {
+    DEVICE_TAGS
+        .iter()
+        .find_map(|(tag, dir)| {
+            let id = device.strip_prefix(tag)?;
+            Some(format!("/dev/disk/{}/{}", dir, id.trim_matches('"')))
+        })
+        .unwrap_or_else(|| device.to_string())
+}
+
+/// Decodes the octal escapes fstab uses for whitespace, e.g. `\040`.
+fn unescape(field: &str) -> String {
+    let mut result = String::new();
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        result.push_str(&rest[..index]);
+        let escape = &rest[index + 1..];
+        match escape
+            .get(..3)
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            Some(byte) => {
+                result.push(byte as char);
+                rest = &escape[3..];
+            }
+            None => {
+                result.push('\\');
+                rest = escape;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Converts `/etc/fstab` into NixOS `fileSystems` and `swapDevices`.
+pub struct FstabConverter {}
+
+impl Default for FstabConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FstabConverter {
+    pub fn new() -> FstabConverter {
+        FstabConverter {}
+    }
+
+    fn options(options: &str) -> Vec<String> {
+        options
+            .split(',')
+            .filter(|option| !option.is_empty() && *option != "defaults")
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn list(options: Vec<String>) -> NixVariableValue {
+        NixVariableValue::List(options.into_iter().map(NixVariableValue::String).collect())
+    }
+
+    fn swap_device(device: String, options: Vec<String>) -> NixVariableValue {
+        let mut swap = IndexMap::from([("device".to_string(), NixVariableValue::String(device))]);
+        let mut remaining = vec![];
+        for option in options {
+            match option.split_once('=') {
+                Some(("pri", priority)) if priority.parse::<f64>().is_ok() => {
+                    swap.insert(
+                        "priority".to_string(),
+                        NixVariableValue::Number(priority.parse().unwrap()),
+                    );
+                }
+                Some(("discard", policy)) => {
+                    swap.insert(
+                        "discardPolicy".to_string(),
+                        NixVariableValue::String(policy.to_string()),
+                    );
+                }
+                None if option == "discard" => {
+                    swap.insert(
+                        "discardPolicy".to_string(),
+                        NixVariableValue::String("both".to_string()),
+                    );
+                }
+                None if option == "sw" => {}
+                _ => remaining.push(option),
+            }
+        }
+        if !remaining.is_empty() {
+            swap.insert("options".to_string(), FstabConverter::list(remaining));
+        }
+        NixVariableValue::AttributeSet(swap)
+    }
+}
+
+impl Converter for FstabConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::fstab
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if !matches!(target, Target::nixos) {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![]);
+        let mut swap_devices = vec![];
+        for row in rows(variables)? {
+            let field = |column: &str| row.get(column).map(|v| unescape(&plain_string(v)));
+            let device = device_path(&field("device")?);
+            let mount_point = field("mountPoint")?;
+            let fs_type = field("fsType")?;
+            let options = FstabConverter::options(&field("options").unwrap_or_default());
+
+            if fs_type == "swap" {
+                swap_devices.push(FstabConverter::swap_device(device, options));
+                continue;
+            }
+            if MANAGED_MOUNTS.contains(&mount_point.as_str()) {
+                conversion.warnings.push(format!(
+                    "{} is mounted by NixOS and was dropped",
+                    mount_point
+                ));
+                continue;
+            }
+            if field("dump").is_some_and(|dump| dump != "0") {
+                conversion.warnings.push(format!(
+                    "{}: dump is not supported by NixOS and was dropped",
+                    mount_point
+                ));
+            }
+
+            let mut file_system = IndexMap::from([
+                ("device".to_string(), NixVariableValue::String(device)),
+                (
+                    "fsType".to_string(),
+                    NixVariableValue::String(fs_type.clone()),
+                ),
+            ]);
+            if !options.is_empty() {
+                file_system.insert("options".to_string(), FstabConverter::list(options));
+            }
+            if field("pass").is_some_and(|pass| pass == "0")
+                && !UNCHECKED_TYPES.contains(&fs_type.as_str())
+            {
+                file_system.insert("noCheck".to_string(), NixVariableValue::Boolean(true));
+            }
+            conversion.variables.push(NixVariable::new(
+                &format!("fileSystems.{}", attr_name(&mount_point)),
+                &NixVariableValue::AttributeSet(file_system),
+            ));
+        }
+        if !swap_devices.is_empty() {
+            conversion.variables.push(NixVariable::new(
+                "swapDevices",
+                &NixVariableValue::List(swap_devices),
+            ));
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{fstab::FstabConverter, Converter, Target};
+    use crate::parser::{columns::ColumnParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_fstab() {
+        let parser = ColumnParser::new(
+            &["device", "mountPoint", "fsType", "options", "dump", "pass"],
+            3,
+        );
+        let parsed = parser.parse(FSTAB).unwrap();
+        assert!(FstabConverter::new()
+            .convert("fstab", &parsed, &Target::home_manager)
+            .is_none());
+        let converted = FstabConverter::new().convert("fstab", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "/: dump is not supported by NixOS and was dropped",
+                "/proc is mounted by NixOS and was dropped",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const FSTAB: &str = "
+# <file system> <mount point> <type> <options> <dump> <pass>
+UUID=0a3407de-014b-458b-b5c1-848e92a327a3 / ext4 noatime,errors=remount-ro 1 1
+LABEL=boot /boot vfat umask=0077 0 2
+/dev/sdb1 /mnt/My\\040Files ntfs-3g defaults 0 0
+proc /proc proc defaults 0 0
+/dev/sda3 none swap sw,pri=10 0 0
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+fileSystems.\"/\" = {
+device = \"/dev/disk/by-uuid/0a3407de-014b-458b-b5c1-848e92a327a3\";
+fsType = \"ext4\";
+options = [
+\"noatime\"
+\"errors=remount-ro\"
+];
+};
+fileSystems.\"/boot\" = {
+device = \"/dev/disk/by-label/boot\";
+fsType = \"vfat\";
+options = [
+\"umask=0077\"
+];
+};
+fileSystems.\"/mnt/My Files\" = {
+device = \"/dev/sdb1\";
+fsType = \"ntfs-3g\";
+noCheck = true;
+};
+swapDevices = [
+{
+device = \"/dev/sda3\";
+priority = 10;
+}
+];
+}";
+}
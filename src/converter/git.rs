@@ -0,0 +1,235 @@
+use super::{Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Converts a `.gitconfig` into the options of the `programs.git` module.
+pub struct GitConverter {}
+
+impl Default for GitConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitConverter {
+    pub fn new() -> GitConverter {
+        GitConverter {}
+    }
+
+    /// Turns the `path` entries of an include section into `includes` entries.
+    fn includes(section: &NixVariableValue, condition: Option<&str>) -> Vec<NixVariableValue> {
+        let paths = match section {
+            NixVariableValue::AttributeSet(set) => match set.get("path") {
+                Some(NixVariableValue::List(paths)) => paths.to_owned(),
+                Some(path) => vec![path.to_owned()],
+                None => vec![],
+            },
+            _ => vec![],
+        };
+        paths
+            .into_iter()
+            .map(|path| {
+                let mut include = IndexMap::from([("path".to_string(), path)]);
+                if let Some(condition) = condition {
+                    include.insert(
+                        "condition".to_string(),
+                        NixVariableValue::String(condition.to_string()),
+                    );
+                }
+                NixVariableValue::AttributeSet(include)
+            })
+            .collect()
+    }
+}
+
+impl Converter for GitConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::ini
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut options = IndexMap::new();
+        let mut aliases = IndexMap::new();
+        let mut signing = IndexMap::new();
+        let mut includes = vec![];
+        let mut extra_config = IndexMap::new();
+
+        for variable in variables {
+            let mut section = match &variable.value {
+                NixVariableValue::AttributeSet(set) => set.to_owned(),
+                value => {
+                    extra_config.insert(variable.name.to_owned(), value.to_owned());
+                    continue;
+                }
+            };
+            match variable.name.to_lowercase().as_str() {
+                "user" => {
+                    for (key, value) in section {
+                        match key.to_lowercase().as_str() {
+                            "name" => {
+                                options.insert("userName", value);
+                            }
+                            "email" => {
+                                options.insert("userEmail", value);
+                            }
+                            "signingkey" => {
+                                signing.insert("key".to_string(), value);
+                            }
+                            _ => {
+                                if let NixVariableValue::AttributeSet(user) =
+                                    extra_config.entry("user".to_string()).or_insert_with(|| {
+                                        NixVariableValue::AttributeSet(IndexMap::new())
+                                    })
+                                {
+                                    user.insert(key, value);
+                                }
+                            }
+                        }
+                    }
+                    continue;
+                }
+                "alias" => {
+                    aliases.extend(section);
+                    continue;
+                }
+                "include" => {
+                    includes.extend(GitConverter::includes(&variable.value, None));
+                    continue;
+                }
+                "includeif" => {
+                    for (condition, value) in section {
+                        includes.extend(GitConverter::includes(&value, Some(&condition)));
+                    }
+                    continue;
+                }
+                "commit" => {
+                    if let Some(key) = section
+                        .keys()
+                        .find(|key| key.eq_ignore_ascii_case("gpgsign"))
+                        .cloned()
+                    {
+                        signing.insert(
+                            "signByDefault".to_string(),
+                            section.shift_remove(&key).unwrap(),
+                        );
+                    }
+                }
+                _ => {}
+            }
+            if !section.is_empty() {
+                extra_config.insert(
+                    variable.name.to_owned(),
+                    NixVariableValue::AttributeSet(section),
+                );
+            }
+        }
+
+        if !aliases.is_empty() {
+            options.insert("aliases", NixVariableValue::AttributeSet(aliases));
+        }
+        if !signing.is_empty() {
+            options.insert("signing", NixVariableValue::AttributeSet(signing));
+        }
+        if !includes.is_empty() {
+            options.insert("includes", NixVariableValue::List(includes));
+        }
+        if !extra_config.is_empty() {
+            options.insert("extraConfig", NixVariableValue::AttributeSet(extra_config));
+        }
+        Some(Conversion::new(
+            vec![NixVariable::new(
+                "programs.git.enable",
+                &NixVariableValue::Boolean(true),
+            )]
+            .into_iter()
+            .chain(options.into_iter().map(|(option, value)| {
+                NixVariable::new(&format!("programs.git.{}", option), &value)
+            }))
+            .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{git::GitConverter, Converter, Target};
+    use crate::parser::{ini::IniParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_git() {
+        let parsed = IniParser::new().parse(GITCONFIG).unwrap();
+        let converted = GitConverter::new().convert("git", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const GITCONFIG: &str = "
+[user]
+    name = Jane Doe
+    email = jane@example.com
+    signingkey = ABCDEF
+[commit]
+    gpgsign = true
+    verbose = true
+[alias]
+    co = checkout
+    lg = \"log --graph --format='%h %s'\"
+[include]
+    path = ~/.gitconfig.local
+[includeIf \"gitdir:~/work/\"]
+    path = ~/work/.gitconfig
+[url \"git@github.com:\"]
+    insteadOf = https://github.com/
+[pull]
+    rebase = true
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+programs.git.enable = true;
+programs.git.userName = \"Jane Doe\";
+programs.git.userEmail = \"jane@example.com\";
+programs.git.aliases = {
+co = \"checkout\";
+lg = \"log --graph --format='%h %s'\";
+};
+programs.git.signing = {
+key = \"ABCDEF\";
+signByDefault = true;
+};
+programs.git.includes = [
+{
+path = \"~/.gitconfig.local\";
+}
+{
+path = \"~/work/.gitconfig\";
+condition = \"gitdir:~/work/\";
+}
+];
+programs.git.extraConfig = {
+commit = {
+verbose = true;
+};
+url = {
+\"git@github.com:\" = {
+insteadOf = \"https://github.com/\";
+};
+};
+pull = {
+rebase = true;
+};
+};
+}";
+}
@@ -0,0 +1,120 @@
+use super::{plain_string, rows, Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Converts `/etc/hosts` into `networking.hosts`.
+///
+/// Host names of repeated addresses are merged. The `localhost` entries are
+/// dropped, NixOS generates them by itself.
+pub struct HostsConverter {}
+
+impl Default for HostsConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HostsConverter {
+    pub fn new() -> HostsConverter {
+        HostsConverter {}
+    }
+
+    fn generated(address: &str, hostname: &str) -> bool {
+        ["127.0.0.1", "::1"].contains(&address)
+            && [
+                "localhost",
+                "localhost.localdomain",
+                "ip6-localhost",
+                "ip6-loopback",
+            ]
+            .contains(&hostname)
+    }
+}
+
+impl Converter for HostsConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::hosts
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if !matches!(target, Target::nixos) {
+            return None;
+        }
+        let mut hosts: IndexMap<String, Vec<NixVariableValue>> = IndexMap::new();
+        for row in rows(variables)? {
+            let address = plain_string(row.get("address")?);
+            let Some(NixVariableValue::List(hostnames)) = row.get("hostnames") else {
+                return None;
+            };
+            let hostnames: Vec<NixVariableValue> = hostnames
+                .iter()
+                .filter(|hostname| !HostsConverter::generated(&address, &plain_string(hostname)))
+                .cloned()
+                .collect();
+            if hostnames.is_empty() {
+                continue;
+            }
+            let entry = hosts.entry(address).or_default();
+            for hostname in hostnames {
+                if !entry.contains(&hostname) {
+                    entry.push(hostname);
+                }
+            }
+        }
+        Some(Conversion::new(vec![NixVariable::new(
+            "networking.hosts",
+            &NixVariableValue::AttributeSet(
+                hosts
+                    .into_iter()
+                    .map(|(address, hostnames)| (address, NixVariableValue::List(hostnames)))
+                    .collect(),
+            ),
+        )]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{hosts::HostsConverter, Converter, Target};
+    use crate::parser::{columns::ColumnParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_hosts() {
+        let parser = ColumnParser::new(&["address", "hostnames"], 2).with_variadic_last_column();
+        let parsed = parser.parse(HOSTS).unwrap();
+        let converted = HostsConverter::new().convert("hosts", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const HOSTS: &str = "
+127.0.0.1 localhost
+::1 localhost ip6-localhost ip6-loopback
+127.0.1.1 workstation
+192.168.1.10 nas nas.lan # storage
+192.168.1.10 backup
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+networking.hosts = {
+\"127.0.1.1\" = [
+\"workstation\"
+];
+\"192.168.1.10\" = [
+\"nas\"
+\"nas.lan\"
+\"backup\"
+];
+};
+}";
+}
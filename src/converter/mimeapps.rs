@@ -0,0 +1,142 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// Sections of `mimeapps.list` and the `xdg.mimeApps` options they map to.
+const SECTIONS: [(&str, &str); 3] = [
+    ("Default Applications", "defaultApplications"),
+    ("Added Associations", "associations.added"),
+    ("Removed Associations", "associations.removed"),
+];
+
+/// Converts `mimeapps.list` into the `xdg.mimeApps` options.
+///
+/// The `;` separated lists of desktop entries are split into nix lists.
+pub struct MimeAppsConverter {}
+
+impl Default for MimeAppsConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MimeAppsConverter {
+    pub fn new() -> MimeAppsConverter {
+        MimeAppsConverter {}
+    }
+
+    /// Splits all values of a possibly repeated key into desktop entries.
+    fn entries(value: &NixVariableValue) -> Vec<NixVariableValue> {
+        match value {
+            NixVariableValue::List(l) => l.iter().flat_map(MimeAppsConverter::entries).collect(),
+            value => plain_string(value)
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| NixVariableValue::String(entry.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Converter for MimeAppsConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::mimeapps
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![NixVariable::new(
+            "xdg.mimeApps.enable",
+            &NixVariableValue::Boolean(true),
+        )]);
+        for section in variables {
+            let NixVariableValue::AttributeSet(associations) = &section.value else {
+                return None;
+            };
+            let Some((_, option)) = SECTIONS.iter().find(|(name, _)| *name == section.name) else {
+                conversion.warnings.push(format!(
+                    "[{}] is no mimeapps.list section and was dropped",
+                    section.name
+                ));
+                continue;
+            };
+            let associations: IndexMap<String, NixVariableValue> = associations
+                .iter()
+                .map(|(mime_type, value)| {
+                    (
+                        mime_type.to_owned(),
+                        NixVariableValue::List(MimeAppsConverter::entries(value)),
+                    )
+                })
+                .collect();
+            conversion.variables.push(NixVariable::new(
+                &format!("xdg.mimeApps.{}", option),
+                &NixVariableValue::AttributeSet(associations),
+            ));
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{mimeapps::MimeAppsConverter, Converter, Target};
+    use crate::parser::{systemd::SystemdUnitParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_mimeapps() {
+        let parsed = SystemdUnitParser::new().parse(MIMEAPPS).unwrap();
+        let converted =
+            MimeAppsConverter::new().convert("mimeapps.list", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const MIMEAPPS: &str = "
+[Default Applications]
+text/html=firefox.desktop
+x-scheme-handler/mailto=thunderbird.desktop;
+
+[Added Associations]
+application/pdf=org.pwmt.zathura.desktop;org.gnome.Evince.desktop;
+
+[Removed Associations]
+image/png=gimp.desktop
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+xdg.mimeApps.enable = true;
+xdg.mimeApps.defaultApplications = {
+\"text/html\" = [
+\"firefox.desktop\"
+];
+\"x-scheme-handler/mailto\" = [
+\"thunderbird.desktop\"
+];
+};
+xdg.mimeApps.associations.added = {
+\"application/pdf\" = [
+\"org.pwmt.zathura.desktop\"
+\"org.gnome.Evince.desktop\"
+];
+};
+xdg.mimeApps.associations.removed = {
+\"image/png\" = [
+\"gimp.desktop\"
+];
+};
+}";
+}
@@ -0,0 +1,132 @@
+pub mod compose;
+pub mod cron;
+pub mod crypttab;
+pub mod daemon;
+pub mod firefox;
+pub mod fstab;
+pub mod git;
+pub mod hosts;
+pub mod mimeapps;
+pub mod packages;
+pub mod shell;
+pub mod ssh;
+pub mod systemd;
+pub mod vscode;
+pub mod xresources;
+
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use clap::ValueEnum;
+use indexmap::IndexMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum SupportedConverters {
+    bash,
+    compose,
+    crontab,
+    cron_jobs,
+    crypttab,
+    firefox,
+    fish,
+    fstab,
+    git,
+    hosts,
+    mimeapps,
+    packages,
+    ssh,
+    sshd,
+    systemd,
+    vscode,
+    xresources,
+    zsh,
+}
+
+/// The configuration system the generated module is written for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum Target {
+    home_manager,
+    nixos,
+}
+
+/// The result of a conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    /// The converted options, named by their full option path.
+    pub variables: Vec<NixVariable>,
+    /// Parts of the input that could not be converted exactly.
+    pub warnings: Vec<String>,
+}
+
+impl Conversion {
+    pub fn new(variables: Vec<NixVariable>) -> Conversion {
+        Conversion {
+            variables,
+            warnings: vec![],
+        }
+    }
+}
+
+/// Maps the parsed content of a program specific configuration file onto the
+/// options of the matching nix module.
+///
+/// The names of the returned variables are full option paths, so the result
+/// is meant to be passed to [`ExpressionGenerator::generate_nix_module`](crate::parser::ExpressionGenerator::generate_nix_module).
+pub trait Converter {
+    /// The format the converted files are written in.
+    fn format(&self) -> SupportedFormats;
+    /// Converts the parsed file. Returns `None` if the file can't be
+    /// converted for the given target.
+    fn convert(&self, name: &str, variables: &[NixVariable], target: &Target)
+        -> Option<Conversion>;
+}
+
+/// Splits a value into words, keeping quoted words together.
+pub(crate) fn split_words(value: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quote = None;
+    for c in value.chars() {
+        match c {
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            c if quote == Some(c) => quote = None,
+            c if c.is_whitespace() && quote.is_none() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Returns scalar values as they were written in the source file, without
+/// the quotes of nix strings.
+pub(crate) fn plain_string(value: &NixVariableValue) -> String {
+    match value {
+        NixVariableValue::String(s) => s.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+/// Returns the rows of a file parsed by a
+/// [`ColumnParser`](crate::parser::columns::ColumnParser).
+pub(crate) fn rows(variables: &[NixVariable]) -> Option<Vec<&IndexMap<String, NixVariableValue>>> {
+    match variables {
+        [NixVariable {
+            name,
+            value: NixVariableValue::List(rows),
+        }] if name == "rows" => rows
+            .iter()
+            .map(|row| match row {
+                NixVariableValue::AttributeSet(row) => Some(row),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
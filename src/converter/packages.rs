@@ -0,0 +1,266 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// The bundled mapping of package names to nixpkgs attributes.
+const PACKAGE_TABLE: &str = include_str!("packages.txt");
+
+/// Suffixes that are dropped when guessing the nixpkgs name of a package.
+const SUFFIXES: [&str; 10] = [
+    "-bin", "-git", "-cli", "-nox", "-gtk", "-full", "-devel", "-dev", "-tools", "-utils",
+];
+
+/// Parses a mapping table. Every line starts with the nixpkgs attribute,
+/// followed by the names used by other package managers.
+pub(crate) fn parse_table(content: &str) -> IndexMap<String, String> {
+    let mut table = IndexMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut names = line.split_whitespace();
+        let Some(attribute) = names.next() else {
+            continue;
+        };
+        table.insert(attribute.to_lowercase(), attribute.to_string());
+        for name in names {
+            table.insert(name.to_lowercase(), attribute.to_string());
+        }
+    }
+    table
+}
+
+/// The Levenshtein distance between two names.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How a package was matched to a nixpkgs attribute.
+enum Match {
+    Exact(String),
+    Guessed(String),
+    None,
+}
+
+/// Converts lists of installed packages into `home.packages` or
+/// `environment.systemPackages`.
+///
+/// Names are looked up in the override table, then the bundled table. Python
+/// and node packages fall back to `python3Packages` and `nodePackages`, and
+/// system packages that are not in the tables are matched fuzzily. Both are
+/// reported as a warning. Packages without a match are kept as comments.
+pub struct PackagesConverter {
+    table: IndexMap<String, String>,
+    overrides: IndexMap<String, String>,
+}
+
+impl Default for PackagesConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PackagesConverter {
+    pub fn new() -> PackagesConverter {
+        PackagesConverter {
+            table: parse_table(PACKAGE_TABLE),
+            overrides: IndexMap::new(),
+        }
+    }
+
+    /// Adds a mapping table that takes precedence over the bundled one. It
+    /// uses the same format: a nixpkgs attribute followed by the names of the
+    /// package elsewhere on every line.
+    pub fn with_overrides(mut self, content: &str) -> PackagesConverter {
+        self.overrides.extend(parse_table(content));
+        self
+    }
+
+    fn python_package(name: &str) -> String {
+        format!(
+            "python3Packages.{}",
+            attr_name(&name.to_lowercase().replace(['_', '.'], "-"))
+        )
+    }
+
+    fn node_package(name: &str) -> Option<String> {
+        (!name.starts_with('@')).then(|| format!("nodePackages.{}", attr_name(name)))
+    }
+
+    /// Guesses the attribute of a system package that is not in the tables.
+    fn guess(&self, name: &str) -> Option<String> {
+        if let Some(package) = ["python3-", "python-", "py3-"]
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+        {
+            return Some(PackagesConverter::python_package(package));
+        }
+        if let Some(package) = name.strip_prefix("node-") {
+            return PackagesConverter::node_package(package);
+        }
+        let versionless = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let candidates = SUFFIXES
+            .iter()
+            .filter_map(|suffix| name.strip_suffix(suffix))
+            .chain([versionless, versionless.trim_end_matches('-')]);
+        for candidate in candidates {
+            if let Some(attribute) = self.table.get(candidate) {
+                return Some(attribute.to_owned());
+            }
+        }
+        if name.len() < 5 {
+            return None;
+        }
+        self.table
+            .iter()
+            .find(|(known, _)| known.len() >= 5 && distance(name, known) <= 1)
+            .map(|(_, attribute)| attribute.to_owned())
+    }
+
+    fn resolve(&self, ecosystem: &str, name: &str) -> Match {
+        let key = name.to_lowercase();
+        if let Some(attribute) = self.overrides.get(&key).or(self.table.get(&key)) {
+            return Match::Exact(attribute.to_owned());
+        }
+        let attribute = match ecosystem {
+            "python" => Some(PackagesConverter::python_package(name)),
+            "node" => PackagesConverter::node_package(name),
+            _ => return self.guess(&key).map_or(Match::None, Match::Guessed),
+        };
+        // The attribute is constructed, nothing checked that it exists
+        attribute.map_or(Match::None, Match::Guessed)
+    }
+}
+
+impl Converter for PackagesConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::package_list
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        let mut conversion = Conversion::new(vec![]);
+        let mut packages = vec![];
+        let mut unmatched = vec![];
+        for variable in variables {
+            let NixVariableValue::List(names) = &variable.value else {
+                return None;
+            };
+            for name in names.iter().map(plain_string) {
+                let attribute = match self.resolve(&variable.name, &name) {
+                    Match::Exact(attribute) => attribute,
+                    Match::Guessed(attribute) => {
+                        conversion.warnings.push(format!(
+                            "{} was guessed to be {}, please check",
+                            name, attribute
+                        ));
+                        attribute
+                    }
+                    Match::None => {
+                        unmatched.push(NixVariableValue::Comment(format!("unmatched: {}", name)));
+                        continue;
+                    }
+                };
+                let attribute = NixVariableValue::Expression(attribute);
+                if !packages.contains(&attribute) {
+                    packages.push(attribute);
+                }
+            }
+        }
+        if !unmatched.is_empty() {
+            conversion.warnings.push(format!(
+                "{} packages have no nixpkgs match and were kept as comments",
+                unmatched.len()
+            ));
+        }
+        packages.extend(unmatched);
+        let option = match target {
+            Target::home_manager => "home.packages",
+            Target::nixos => "environment.systemPackages",
+        };
+        conversion.variables.push(NixVariable::new(
+            option,
+            &NixVariableValue::With(
+                "pkgs".to_string(),
+                Box::new(NixVariableValue::List(packages)),
+            ),
+        ));
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{packages::PackagesConverter, Converter, Target};
+    use crate::parser::{packages::PackageListParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_packages() {
+        let parsed = PackageListParser::new().parse(PACKAGES).unwrap();
+        let converted = PackagesConverter::new()
+            .with_overrides("my-tools internal-tools")
+            .convert("packages", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "gcc-12 was guessed to be gcc, please check",
+                "python3-yaml was guessed to be python3Packages.yaml, please check",
+                "ripgrap was guessed to be ripgrep, please check",
+                "requests was guessed to be python3Packages.requests, please check",
+                "Django was guessed to be python3Packages.django, please check",
+                "2 packages have no nixpkgs match and were kept as comments",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const PACKAGES: &str = "
+Listing... Done
+fd-find/jammy,now 8.3.1-1 amd64 [installed]
+gcc-12/jammy,now 12.3.0-1 amd64 [installed]
+python3-yaml/jammy,now 5.4.1-1 amd64 [installed]
+libfoo1/jammy,now 1.0-1 amd64 [installed]
+ripgrap
+internal-tools
+fd
+requests==2.31.0
+Django==5.0
+├── @vue/cli@5.0.8
+└── typescript@5.2.2
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+home.packages = with pkgs; [
+fd
+gcc
+python3Packages.yaml
+ripgrep
+my-tools
+python3Packages.requests
+python3Packages.django
+typescript
+# unmatched: libfoo1
+# unmatched: @vue/cli
+];
+}";
+}
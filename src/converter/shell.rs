@@ -0,0 +1,186 @@
+use super::{Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+
+/// The shells with a home-manager module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum Shell {
+    bash,
+    zsh,
+    fish,
+}
+
+/// Converts the aliases, exported variables and options of a shell rc file
+/// into the options of a home-manager shell module.
+///
+/// Exported variables become `home.sessionVariables`, everything else that
+/// could not be classified is kept in `initExtra` or, for fish,
+/// `interactiveShellInit`.
+pub struct ShellConverter {
+    shell: Shell,
+}
+
+impl ShellConverter {
+    pub fn new(shell: Shell) -> ShellConverter {
+        ShellConverter { shell }
+    }
+
+    fn is_empty(value: &NixVariableValue) -> bool {
+        match value {
+            NixVariableValue::AttributeSet(a) => a.is_empty(),
+            NixVariableValue::List(l) => l.is_empty(),
+            NixVariableValue::IndentedString(s) => s.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// The option a part of the parsed rc file belongs to.
+    fn option(&self, part: &str) -> Option<String> {
+        let option = match (part, self.shell) {
+            ("variables", _) => return Some("home.sessionVariables".to_string()),
+            ("aliases", _) => "shellAliases",
+            ("abbreviations", Shell::fish) => "shellAbbrs",
+            ("shopt", Shell::bash) => "shellOptions",
+            ("setopt", Shell::zsh) => "setOptions",
+            ("init", Shell::fish) => "interactiveShellInit",
+            ("init", _) => "initExtra",
+            _ => return None,
+        };
+        Some(format!("programs.{:?}.{}", self.shell, option))
+    }
+}
+
+impl Converter for ShellConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::shell
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![NixVariable::new(
+            &format!("programs.{:?}.enable", self.shell),
+            &NixVariableValue::Boolean(true),
+        )]);
+        for variable in variables {
+            if ShellConverter::is_empty(&variable.value) {
+                continue;
+            }
+            match self.option(&variable.name) {
+                Some(option) => conversion
+                    .variables
+                    .push(NixVariable::new(&option, &variable.value)),
+                None => conversion.warnings.push(format!(
+                    "{} has no {:?} equivalent and was dropped",
+                    variable.name, self.shell
+                )),
+            }
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{
+        shell::{Shell, ShellConverter},
+        Converter, Target,
+    };
+    use crate::parser::{shell::ShellParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_bash() {
+        let parsed = ShellParser::new().parse(BASHRC).unwrap();
+        let converted =
+            ShellConverter::new(Shell::bash).convert("bashrc", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec!["setopt has no bash equivalent and was dropped"]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED_BASH);
+    }
+
+    #[test]
+    fn test_fish() {
+        let parsed = ShellParser::new().parse(FISH).unwrap();
+        let converted =
+            ShellConverter::new(Shell::fish).convert("config.fish", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED_FISH);
+    }
+
+    const BASHRC: &str = "
+# If not running interactively, don't do anything
+[[ $- != *i* ]] && return
+
+alias ll='ls -la'
+export EDITOR=vim
+shopt -s histappend checkwinsize
+setopt autocd
+PS1='[\\u@\\h \\W]\\$ '
+";
+
+    const EXPECTED_BASH: &str = "{ config, pkgs, ... }:
+{
+programs.bash.enable = true;
+programs.bash.shellAliases = {
+ll = \"ls -la\";
+};
+home.sessionVariables = {
+EDITOR = \"vim\";
+};
+programs.bash.shellOptions = [
+\"histappend\"
+\"checkwinsize\"
+];
+programs.bash.initExtra = ''
+# If not running interactively, don't do anything
+[[ $- != *i* ]] && return
+
+PS1='[\\u@\\h \\W]\\$ '
+'';
+}";
+
+    const FISH: &str = "
+set -gx EDITOR nvim
+alias gs 'git status'
+abbr --add gco git checkout
+function fish_greeting
+    echo \"Hello ${USER}\"
+end
+";
+
+    const EXPECTED_FISH: &str = "{ config, pkgs, ... }:
+{
+programs.fish.enable = true;
+programs.fish.shellAliases = {
+gs = \"git status\";
+};
+programs.fish.shellAbbrs = {
+gco = \"git checkout\";
+};
+home.sessionVariables = {
+EDITOR = \"nvim\";
+};
+programs.fish.interactiveShellInit = ''
+function fish_greeting
+    echo \"Hello ''${USER}\"
+end
+'';
+}";
+}
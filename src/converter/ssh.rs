@@ -0,0 +1,425 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// The type of a known `matchBlocks` option.
+enum OptionType {
+    String,
+    Integer,
+    Boolean,
+    List,
+}
+
+/// Known ssh keywords and the `matchBlocks` options they map to.
+const OPTIONS: [(&str, &str, OptionType); 15] = [
+    ("hostname", "hostname", OptionType::String),
+    ("user", "user", OptionType::String),
+    ("port", "port", OptionType::Integer),
+    ("identityfile", "identityFile", OptionType::List),
+    ("identitiesonly", "identitiesOnly", OptionType::Boolean),
+    ("certificatefile", "certificateFile", OptionType::List),
+    ("forwardagent", "forwardAgent", OptionType::Boolean),
+    ("forwardx11", "forwardX11", OptionType::Boolean),
+    (
+        "forwardx11trusted",
+        "forwardX11Trusted",
+        OptionType::Boolean,
+    ),
+    ("proxyjump", "proxyJump", OptionType::String),
+    ("proxycommand", "proxyCommand", OptionType::String),
+    (
+        "serveraliveinterval",
+        "serverAliveInterval",
+        OptionType::Integer,
+    ),
+    (
+        "serveralivecountmax",
+        "serverAliveCountMax",
+        OptionType::Integer,
+    ),
+    ("compression", "compression", OptionType::Boolean),
+    ("addressfamily", "addressFamily", OptionType::String),
+];
+
+/// Forwarding keywords and the `matchBlocks` options listing them.
+const FORWARDS: [(&str, &str); 3] = [
+    ("localforward", "localForwards"),
+    ("remoteforward", "remoteForwards"),
+    ("dynamicforward", "dynamicForwards"),
+];
+
+/// Converts an OpenSSH client configuration into `programs.ssh` options.
+///
+/// Home-manager orders match blocks as a DAG, so every block after the first
+/// one is declared with `lib.hm.dag.entryAfter` to keep the original order.
+pub struct SshConverter {}
+
+impl Default for SshConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshConverter {
+    pub fn new() -> SshConverter {
+        SshConverter {}
+    }
+
+    /// Returns the first value of a possibly repeated keyword as a string.
+    fn first(value: &NixVariableValue) -> String {
+        match value {
+            NixVariableValue::List(l) => l.first().map(SshConverter::first).unwrap_or_default(),
+            value => plain_string(value),
+        }
+    }
+
+    fn convert_value(value: &NixVariableValue, option_type: &OptionType) -> NixVariableValue {
+        let first = SshConverter::first(value);
+        match option_type {
+            OptionType::String => NixVariableValue::String(first),
+            OptionType::Integer => match first.parse::<i64>() {
+                Ok(i) => NixVariableValue::Number(i as f64),
+                Err(_) => NixVariableValue::String(first),
+            },
+            OptionType::Boolean => match first.to_lowercase().as_str() {
+                "yes" | "true" => NixVariableValue::Boolean(true),
+                "no" | "false" => NixVariableValue::Boolean(false),
+                _ => NixVariableValue::String(first),
+            },
+            OptionType::List => match value {
+                NixVariableValue::List(_) => value.to_owned(),
+                value => NixVariableValue::List(vec![value.to_owned()]),
+            },
+        }
+    }
+
+    /// Returns the values of a possibly repeated keyword as strings.
+    fn all(value: &NixVariableValue) -> Vec<String> {
+        match value {
+            NixVariableValue::List(l) => l.iter().map(plain_string).collect(),
+            value => vec![plain_string(value)],
+        }
+    }
+
+    /// Converts an `[address:]port` forwarding endpoint, where the address
+    /// may be an IPv6 address in brackets.
+    fn endpoint(endpoint: &str) -> NixVariableValue {
+        let (address, port) = match endpoint.rsplit_once(':') {
+            Some((address, port)) => (Some(address), port),
+            None => (None, endpoint),
+        };
+        let mut options = IndexMap::new();
+        match port.parse::<u16>() {
+            Ok(port) => {
+                if let Some(address) = address {
+                    options.insert(
+                        "address".to_string(),
+                        NixVariableValue::String(
+                            address.trim_matches(|c| c == '[' || c == ']').to_string(),
+                        ),
+                    );
+                }
+                options.insert("port".to_string(), NixVariableValue::Number(port as f64));
+            }
+            // A unix socket
+            Err(_) => {
+                options.insert(
+                    "address".to_string(),
+                    NixVariableValue::String(endpoint.to_string()),
+                );
+            }
+        }
+        NixVariableValue::AttributeSet(options)
+    }
+
+    /// Converts a forwarding, e.g. `8080 localhost:80`, or `None` if it has
+    /// the wrong number of arguments.
+    fn forward(keyword: &str, forward: &str) -> Option<NixVariableValue> {
+        let arguments = forward.split_whitespace().collect::<Vec<&str>>();
+        match (keyword, arguments.as_slice()) {
+            ("dynamicforward", [bind]) => Some(SshConverter::endpoint(bind)),
+            ("localforward" | "remoteforward", [bind, host]) => {
+                Some(NixVariableValue::AttributeSet(IndexMap::from([
+                    ("bind".to_string(), SshConverter::endpoint(bind)),
+                    ("host".to_string(), SshConverter::endpoint(host)),
+                ])))
+            }
+            _ => None,
+        }
+    }
+
+    fn convert_block(
+        name: &str,
+        block: &IndexMap<String, NixVariableValue>,
+        warnings: &mut Vec<String>,
+    ) -> NixVariableValue {
+        let mut options = IndexMap::new();
+        let mut extra_options = IndexMap::new();
+        for (keyword, value) in block {
+            let lowercase = keyword.to_lowercase();
+            if let Some((_, option)) = FORWARDS.iter().find(|(known, _)| *known == lowercase) {
+                let mut forwards = vec![];
+                for forward in SshConverter::all(value) {
+                    match SshConverter::forward(&lowercase, &forward) {
+                        Some(forward) => forwards.push(forward),
+                        None => warnings.push(format!(
+                            "{} {} in {} could not be converted and was dropped",
+                            keyword, forward, name
+                        )),
+                    }
+                }
+                options.insert(option.to_string(), NixVariableValue::List(forwards));
+                continue;
+            }
+            match OPTIONS.iter().find(|(known, _, _)| lowercase == *known) {
+                Some((_, option, option_type)) => {
+                    options.insert(
+                        option.to_string(),
+                        SshConverter::convert_value(value, option_type),
+                    );
+                }
+                None => {
+                    let values = SshConverter::all(value);
+                    if values.len() > 1 {
+                        // ssh uses the first value of most keywords, but
+                        // some like SendEnv accumulate
+                        warnings.push(format!(
+                            "{} is repeated in {}, extraOptions only keeps {} and drops {}",
+                            keyword,
+                            name,
+                            values[0],
+                            values[1..].join(", ")
+                        ));
+                    }
+                    extra_options.insert(
+                        keyword.to_owned(),
+                        NixVariableValue::String(values[0].to_owned()),
+                    );
+                }
+            }
+        }
+        if !extra_options.is_empty() {
+            options.insert(
+                "extraOptions".to_string(),
+                NixVariableValue::AttributeSet(extra_options),
+            );
+        }
+        NixVariableValue::AttributeSet(options)
+    }
+}
+
+impl Converter for SshConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::ssh_config
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut warnings = vec![];
+        let mut extra_config = vec![];
+        // Repeated blocks are merged, ssh uses the first value of a keyword
+        let mut blocks: IndexMap<String, IndexMap<String, NixVariableValue>> = IndexMap::new();
+        for variable in variables {
+            match (&variable.value, blocks.get_mut(&variable.name)) {
+                (NixVariableValue::AttributeSet(block), Some(first)) => {
+                    let mut kept = vec![];
+                    for (keyword, value) in block {
+                        match first
+                            .keys()
+                            .any(|known| known.eq_ignore_ascii_case(keyword))
+                        {
+                            true => kept.push(keyword.as_str()),
+                            false => {
+                                first.insert(keyword.to_owned(), value.to_owned());
+                            }
+                        }
+                    }
+                    warnings.push(match kept.is_empty() {
+                        true => format!(
+                            "{} is repeated and merged into the first one",
+                            variable.name
+                        ),
+                        false => format!(
+                            "{} is repeated and merged into the first one, which keeps its {}",
+                            variable.name,
+                            kept.join(", ")
+                        ),
+                    });
+                }
+                (NixVariableValue::AttributeSet(block), None) => {
+                    blocks.insert(variable.name.to_owned(), block.to_owned());
+                }
+                (value, _) => extra_config.extend(
+                    SshConverter::all(value)
+                        .iter()
+                        .map(|value| format!("{} {}", variable.name, value)),
+                ),
+            }
+        }
+        let mut match_blocks: IndexMap<String, NixVariableValue> = IndexMap::new();
+        for (header, block) in &blocks {
+            let (keyword, criteria) = header.split_once(' ')?;
+            let mut block = SshConverter::convert_block(header, block, &mut warnings);
+            let name = if keyword == "Match" {
+                if let NixVariableValue::AttributeSet(options) = &mut block {
+                    options.insert(
+                        "match".to_string(),
+                        NixVariableValue::String(criteria.to_string()),
+                    );
+                }
+                format!("match {}", criteria)
+            } else {
+                criteria.to_string()
+            };
+            let block = match match_blocks.keys().last() {
+                Some(previous) => NixVariableValue::Function(
+                    "lib.hm.dag.entryAfter".to_string(),
+                    vec![
+                        NixVariableValue::List(vec![NixVariableValue::String(previous.to_owned())]),
+                        block,
+                    ],
+                ),
+                None => block,
+            };
+            match_blocks.insert(name, block);
+        }
+
+        let mut options = vec![NixVariable::new(
+            "programs.ssh.enable",
+            &NixVariableValue::Boolean(true),
+        )];
+        if !match_blocks.is_empty() {
+            options.push(NixVariable::new(
+                "programs.ssh.matchBlocks",
+                &NixVariableValue::AttributeSet(match_blocks),
+            ));
+        }
+        if !extra_config.is_empty() {
+            options.push(NixVariable::new(
+                "programs.ssh.extraConfig",
+                &NixVariableValue::String(extra_config.join("\n")),
+            ));
+        }
+        Some(Conversion {
+            variables: options,
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{ssh::SshConverter, Converter, Target};
+    use crate::parser::{ssh::SshConfigParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_ssh() {
+        let parsed = SshConfigParser::new().parse(SSH_CONFIG).unwrap();
+        let converted = SshConverter::new().convert("ssh", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec![
+                "Host bastion is repeated and merged into the first one, which keeps its Port",
+                "SendEnv is repeated in Host bastion, extraOptions only keeps LANG and drops LC_*",
+            ]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const SSH_CONFIG: &str = "
+AddKeysToAgent yes
+Include config.d/*
+
+Host github.com gitlab.com
+    User git
+    IdentityFile ~/.ssh/id_ed25519
+    ForwardAgent no
+
+Host bastion
+    HostName 10.0.0.1
+    Port 2222
+    StrictHostKeyChecking accept-new
+    SendEnv LANG
+    SendEnv LC_*
+    LocalForward 8080 localhost:80
+    LocalForward [::1]:5432 db.internal:5432
+    DynamicForward 1080
+
+Match host *.internal
+    ProxyJump bastion
+
+Host bastion
+    Port 22
+    User admin
+";
+
+    const EXPECTED: &str = "{ config, lib, pkgs, ... }:
+{
+programs.ssh.enable = true;
+programs.ssh.matchBlocks = {
+\"github.com gitlab.com\" = {
+user = \"git\";
+identityFile = [
+\"~/.ssh/id_ed25519\"
+];
+forwardAgent = false;
+};
+bastion = lib.hm.dag.entryAfter [
+\"github.com gitlab.com\"
+] {
+hostname = \"10.0.0.1\";
+port = 2222;
+localForwards = [
+{
+bind = {
+port = 8080;
+};
+host = {
+address = \"localhost\";
+port = 80;
+};
+}
+{
+bind = {
+address = \"::1\";
+port = 5432;
+};
+host = {
+address = \"db.internal\";
+port = 5432;
+};
+}
+];
+dynamicForwards = [
+{
+port = 1080;
+}
+];
+user = \"admin\";
+extraOptions = {
+StrictHostKeyChecking = \"accept-new\";
+SendEnv = \"LANG\";
+};
+};
+\"match host *.internal\" = lib.hm.dag.entryAfter [
+\"bastion\"
+] {
+proxyJump = \"bastion\";
+match = \"host *.internal\";
+};
+};
+programs.ssh.extraConfig = \"AddKeysToAgent yes\\nInclude config.d/*\";
+}";
+}
@@ -0,0 +1,308 @@
+use super::{split_words, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// `[Unit]` keys with a dedicated NixOS option, all of them are lists of units.
+const UNIT_DEPENDENCIES: [(&str, &str); 9] = [
+    ("After", "after"),
+    ("Before", "before"),
+    ("Wants", "wants"),
+    ("Requires", "requires"),
+    ("Requisite", "requisite"),
+    ("BindsTo", "bindsTo"),
+    ("PartOf", "partOf"),
+    ("Conflicts", "conflicts"),
+    ("OnFailure", "onFailure"),
+];
+
+/// `[Install]` keys with a dedicated NixOS option.
+const INSTALL_OPTIONS: [(&str, &str); 4] = [
+    ("WantedBy", "wantedBy"),
+    ("RequiredBy", "requiredBy"),
+    ("UpheldBy", "upheldBy"),
+    ("Alias", "aliases"),
+];
+
+/// Converts systemd units into `systemd.services`, `systemd.timers` and
+/// `systemd.sockets` for NixOS or their `systemd.user` counterparts for
+/// home-manager.
+///
+/// The unit type is taken from the suffix of the name or, if it has none,
+/// from the sections of the unit.
+pub struct SystemdConverter {}
+
+impl Default for SystemdConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemdConverter {
+    pub fn new() -> SystemdConverter {
+        SystemdConverter {}
+    }
+
+    /// All words of a possibly repeated key.
+    fn words(value: &NixVariableValue) -> Vec<String> {
+        match value {
+            NixVariableValue::List(l) => l.iter().flat_map(SystemdConverter::words).collect(),
+            NixVariableValue::String(s) => split_words(s),
+            _ => vec![],
+        }
+    }
+
+    fn list(value: &NixVariableValue) -> NixVariableValue {
+        NixVariableValue::List(
+            SystemdConverter::words(value)
+                .into_iter()
+                .map(NixVariableValue::String)
+                .collect(),
+        )
+    }
+
+    /// Splits the name into the unit name and the unit type.
+    fn unit_type(name: &str, sections: &IndexMap<String, NixVariableValue>) -> (String, String) {
+        match name.rsplit_once('.') {
+            Some((name, unit_type)) if ["service", "timer", "socket"].contains(&unit_type) => {
+                (name.to_string(), unit_type.to_string())
+            }
+            _ => {
+                let unit_type = ["Timer", "Socket"]
+                    .into_iter()
+                    .find(|section| sections.contains_key(*section))
+                    .unwrap_or("Service");
+                (name.to_string(), unit_type.to_lowercase())
+            }
+        }
+    }
+
+    /// Converts a unit into the options of a NixOS unit.
+    fn convert_nixos(
+        unit_type: &str,
+        sections: &IndexMap<String, NixVariableValue>,
+        warnings: &mut Vec<String>,
+    ) -> IndexMap<String, NixVariableValue> {
+        let mut options = IndexMap::new();
+        let mut unit_config = IndexMap::new();
+        let mut type_config = IndexMap::new();
+        for (section, values) in sections {
+            let NixVariableValue::AttributeSet(values) = values else {
+                continue;
+            };
+            for (key, value) in values {
+                match (section.as_str(), key.as_str()) {
+                    ("Unit", "Description") => {
+                        options.insert("description".to_string(), value.to_owned());
+                    }
+                    ("Unit", "Documentation") => {
+                        options.insert("documentation".to_string(), SystemdConverter::list(value));
+                    }
+                    ("Unit", key) => match UNIT_DEPENDENCIES.iter().find(|(k, _)| *k == key) {
+                        Some((_, option)) => {
+                            options.insert(option.to_string(), SystemdConverter::list(value));
+                        }
+                        None => {
+                            unit_config.insert(key.to_string(), value.to_owned());
+                        }
+                    },
+                    ("Install", key) => match INSTALL_OPTIONS.iter().find(|(k, _)| *k == key) {
+                        Some((_, option)) => {
+                            options.insert(option.to_string(), SystemdConverter::list(value));
+                        }
+                        None => warnings.push(format!(
+                            "[Install] {} has no NixOS equivalent and was dropped",
+                            key
+                        )),
+                    },
+                    ("Service", "Environment") if unit_type == "service" => {
+                        options.insert(
+                            "environment".to_string(),
+                            NixVariableValue::AttributeSet(
+                                SystemdConverter::words(value)
+                                    .iter()
+                                    .filter_map(|assignment| assignment.split_once('='))
+                                    .map(|(name, value)| {
+                                        (
+                                            name.to_string(),
+                                            NixVariableValue::String(value.to_string()),
+                                        )
+                                    })
+                                    .collect(),
+                            ),
+                        );
+                    }
+                    ("Socket", "ListenStream") if unit_type == "socket" => {
+                        options.insert("listenStreams".to_string(), SystemdConverter::list(value));
+                    }
+                    ("Socket", "ListenDatagram") if unit_type == "socket" => {
+                        options
+                            .insert("listenDatagrams".to_string(), SystemdConverter::list(value));
+                    }
+                    (section, key) if section.to_lowercase() == unit_type => {
+                        type_config.insert(key.to_string(), value.to_owned());
+                    }
+                    (section, key) => warnings.push(format!(
+                        "[{}] {} is not part of a {} unit and was dropped",
+                        section, key, unit_type
+                    )),
+                }
+            }
+        }
+        if !unit_config.is_empty() {
+            options.insert(
+                "unitConfig".to_string(),
+                NixVariableValue::AttributeSet(unit_config),
+            );
+        }
+        if !type_config.is_empty() {
+            options.insert(
+                format!("{}Config", unit_type),
+                NixVariableValue::AttributeSet(type_config),
+            );
+        }
+        options
+    }
+}
+
+impl Converter for SystemdConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::systemd
+    }
+
+    fn convert(
+        &self,
+        name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        let sections: IndexMap<String, NixVariableValue> = variables
+            .iter()
+            .map(|variable| (variable.name.to_owned(), variable.value.to_owned()))
+            .collect();
+        let (name, unit_type) = SystemdConverter::unit_type(name, &sections);
+        if name.is_empty() {
+            return None;
+        }
+        let mut warnings = vec![];
+        let (prefix, unit) = match target {
+            // Home-manager takes the sections of the unit as they are
+            Target::home_manager => ("systemd.user", sections),
+            Target::nixos => (
+                "systemd",
+                SystemdConverter::convert_nixos(&unit_type, &sections, &mut warnings),
+            ),
+        };
+        Some(Conversion {
+            variables: vec![NixVariable::new(
+                &format!("{}.{}s.{}", prefix, unit_type, attr_name(&name)),
+                &NixVariableValue::AttributeSet(unit),
+            )],
+            warnings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{systemd::SystemdConverter, Converter, Target};
+    use crate::parser::{systemd::SystemdUnitParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_systemd_nixos() {
+        let parsed = SystemdUnitParser::new().parse(SERVICE).unwrap();
+        let converted = SystemdConverter::new().convert("getty@.service", &parsed, &Target::nixos);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec!["[Install] Also has no NixOS equivalent and was dropped"]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED_SERVICE);
+    }
+
+    #[test]
+    fn test_systemd_home_manager() {
+        let parsed = SystemdUnitParser::new().parse(TIMER).unwrap();
+        let converted = SystemdConverter::new().convert("backup", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+
+        let generated =
+            ExpressionGenerator::new().generate_nix_module(&converted.unwrap().variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED_TIMER);
+    }
+
+    const SERVICE: &str = "
+[Unit]
+Description=Getty on %I
+After=systemd-user-sessions.service plymouth-quit-wait.service
+After=rc-local.service
+ConditionPathExists=/dev/tty0
+
+[Service]
+ExecStart=-/sbin/agetty -o '-p -- \\\\u' --noclear - $TERM
+Environment=\"LANG=C.UTF-8\" TERM=linux
+Restart=always
+
+[Install]
+WantedBy=getty.target
+Also=serial-getty@.service
+";
+
+    const EXPECTED_SERVICE: &str = "{ config, pkgs, ... }:
+{
+systemd.services.\"getty@\" = {
+description = \"Getty on %I\";
+after = [
+\"systemd-user-sessions.service\"
+\"plymouth-quit-wait.service\"
+\"rc-local.service\"
+];
+environment = {
+LANG = \"C.UTF-8\";
+TERM = \"linux\";
+};
+wantedBy = [
+\"getty.target\"
+];
+unitConfig = {
+ConditionPathExists = \"/dev/tty0\";
+};
+serviceConfig = {
+ExecStart = \"-/sbin/agetty -o '-p -- \\\\\\\\u' --noclear - $TERM\";
+Restart = \"always\";
+};
+};
+}";
+
+    const TIMER: &str = "
+[Unit]
+Description=Daily backup
+
+[Timer]
+OnCalendar=daily
+Persistent=true
+
+[Install]
+WantedBy=timers.target
+";
+
+    const EXPECTED_TIMER: &str = "{ config, pkgs, ... }:
+{
+systemd.user.timers.backup = {
+Unit = {
+Description = \"Daily backup\";
+};
+Timer = {
+OnCalendar = \"daily\";
+Persistent = \"true\";
+};
+Install = {
+WantedBy = \"timers.target\";
+};
+};
+}";
+}
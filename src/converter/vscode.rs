@@ -0,0 +1,208 @@
+use super::{packages::parse_table, plain_string, Conversion, Converter, Target};
+use crate::parser::{attr_name, NixVariable, NixVariableValue, SupportedFormats};
+use indexmap::IndexMap;
+
+/// The bundled list of VS Code extensions packaged in nixpkgs.
+const EXTENSION_TABLE: &str = include_str!("vscode-extensions.txt");
+
+/// Converts VS Code user configuration into `programs.vscode` options.
+///
+/// The kind of file is detected from its content: `extensions.json` lists
+/// `recommendations`, `keybindings.json` is an array and everything else is
+/// taken as `settings.json`. Extensions are mapped to
+/// `pkgs.vscode-extensions`, unknown extensions are kept as comments.
+pub struct VscodeConverter {
+    extensions: IndexMap<String, String>,
+}
+
+impl Default for VscodeConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VscodeConverter {
+    pub fn new() -> VscodeConverter {
+        VscodeConverter {
+            extensions: parse_table(EXTENSION_TABLE),
+        }
+    }
+
+    fn extensions(&self, ids: &[NixVariableValue], warnings: &mut Vec<String>) -> NixVariableValue {
+        let mut extensions = vec![];
+        let mut unmatched = vec![];
+        for id in ids.iter().map(plain_string) {
+            match self.extensions.get(&id.to_lowercase()) {
+                Some(attribute) => extensions.push(NixVariableValue::Expression(
+                    attribute
+                        .split('.')
+                        .map(attr_name)
+                        .collect::<Vec<String>>()
+                        .join("."),
+                )),
+                None => unmatched.push(NixVariableValue::Comment(format!("unmatched: {}", id))),
+            }
+        }
+        if !unmatched.is_empty() {
+            warnings.push(format!(
+                "{} extensions are not packaged in nixpkgs and were kept as comments",
+                unmatched.len()
+            ));
+        }
+        extensions.extend(unmatched);
+        NixVariableValue::With(
+            "pkgs.vscode-extensions".to_string(),
+            Box::new(NixVariableValue::List(extensions)),
+        )
+    }
+}
+
+impl Converter for VscodeConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::jsonc
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![NixVariable::new(
+            "programs.vscode.enable",
+            &NixVariableValue::Boolean(true),
+        )]);
+        let recommendations = variables
+            .iter()
+            .find(|variable| variable.name == "recommendations");
+        match (variables, recommendations) {
+            ([NixVariable { name, value }], _) if name == "items" => {
+                conversion
+                    .variables
+                    .push(NixVariable::new("programs.vscode.keybindings", value));
+            }
+            (_, Some(recommendations)) => {
+                let NixVariableValue::List(ids) = &recommendations.value else {
+                    return None;
+                };
+                let extensions = self.extensions(ids, &mut conversion.warnings);
+                conversion
+                    .variables
+                    .push(NixVariable::new("programs.vscode.extensions", &extensions));
+            }
+            _ => conversion.variables.push(NixVariable::new(
+                "programs.vscode.userSettings",
+                &NixVariableValue::AttributeSet(
+                    variables
+                        .iter()
+                        .map(|setting| (setting.name.to_owned(), setting.value.to_owned()))
+                        .collect(),
+                ),
+            )),
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{vscode::VscodeConverter, Converter, Target};
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+
+    fn convert(content: &str) -> (String, Vec<String>) {
+        let parsed = JsonParser::new().with_comments().parse(content).unwrap();
+        let converted = VscodeConverter::new()
+            .convert("vscode", &parsed, &Target::home_manager)
+            .unwrap();
+        (
+            ExpressionGenerator::new()
+                .generate_nix_module(&converted.variables)
+                .unwrap(),
+            converted.warnings,
+        )
+    }
+
+    #[test]
+    fn test_vscode_settings() {
+        assert_eq!(convert(SETTINGS), (EXPECTED_SETTINGS.to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_vscode_keybindings_and_extensions() {
+        assert_eq!(
+            convert(KEYBINDINGS),
+            (EXPECTED_KEYBINDINGS.to_string(), vec![])
+        );
+        assert_eq!(
+            convert(EXTENSIONS),
+            (
+                EXPECTED_EXTENSIONS.to_string(),
+                vec![
+                    "1 extensions are not packaged in nixpkgs and were kept as comments"
+                        .to_string()
+                ]
+            )
+        );
+    }
+
+    const SETTINGS: &str = "{
+    // Editor
+    \"editor.fontSize\": 14,
+    \"editor.rulers\": [80, 100],
+    \"[rust]\": {
+        \"editor.formatOnSave\": true,
+    },
+}";
+
+    const EXPECTED_SETTINGS: &str = "{ config, pkgs, ... }:
+{
+programs.vscode.enable = true;
+programs.vscode.userSettings = {
+\"[rust]\" = {
+\"editor.formatOnSave\" = true;
+};
+\"editor.fontSize\" = 14;
+\"editor.rulers\" = [
+80
+100
+];
+};
+}";
+
+    const KEYBINDINGS: &str = "// Place your key bindings in this file
+[
+    { \"key\": \"ctrl+alt+t\", \"command\": \"workbench.action.terminal.new\" },
+]";
+
+    const EXPECTED_KEYBINDINGS: &str = "{ config, pkgs, ... }:
+{
+programs.vscode.enable = true;
+programs.vscode.keybindings = [
+{
+command = \"workbench.action.terminal.new\";
+key = \"ctrl+alt+t\";
+}
+];
+}";
+
+    const EXTENSIONS: &str = "{
+    \"recommendations\": [
+        \"rust-lang.rust-analyzer\",
+        \"ms-python.python\",
+        \"acme.internal-linter\"
+    ]
+}";
+
+    const EXPECTED_EXTENSIONS: &str = "{ config, pkgs, ... }:
+{
+programs.vscode.enable = true;
+programs.vscode.extensions = with pkgs.vscode-extensions; [
+rust-lang.rust-analyzer
+ms-python.python
+# unmatched: acme.internal-linter
+];
+}";
+}
@@ -0,0 +1,90 @@
+use super::{plain_string, Conversion, Converter, Target};
+use crate::parser::{NixVariable, NixVariableValue, SupportedFormats};
+
+/// Converts X resources into `xresources.properties`.
+///
+/// Preprocessor directives that were not resolved while parsing, like
+/// `#include`, are reported as warnings.
+pub struct XresourcesConverter {}
+
+impl Default for XresourcesConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XresourcesConverter {
+    pub fn new() -> XresourcesConverter {
+        XresourcesConverter {}
+    }
+}
+
+impl Converter for XresourcesConverter {
+    fn format(&self) -> SupportedFormats {
+        SupportedFormats::xresources
+    }
+
+    fn convert(
+        &self,
+        _name: &str,
+        variables: &[NixVariable],
+        target: &Target,
+    ) -> Option<Conversion> {
+        if *target != Target::home_manager {
+            return None;
+        }
+        let mut conversion = Conversion::new(vec![]);
+        for variable in variables {
+            match (variable.name.as_str(), &variable.value) {
+                ("properties", properties) => conversion
+                    .variables
+                    .push(NixVariable::new("xresources.properties", properties)),
+                ("unresolved", NixVariableValue::List(directives)) => conversion.warnings.extend(
+                    directives
+                        .iter()
+                        .map(|directive| format!("{} was not resolved", plain_string(directive))),
+                ),
+                _ => return None,
+            }
+        }
+        Some(conversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::{xresources::XresourcesConverter, Converter, Target};
+    use crate::parser::{xresources::XresourcesParser, ExpressionGenerator, Parser};
+
+    #[test]
+    fn test_xresources() {
+        let parsed = XresourcesParser::new().parse(XRESOURCES).unwrap();
+        let converted =
+            XresourcesConverter::new().convert("Xresources", &parsed, &Target::home_manager);
+        assert!(converted.is_some());
+        let converted = converted.unwrap();
+        assert_eq!(
+            converted.warnings,
+            vec!["#include \"colors\" was not resolved"]
+        );
+
+        let generated = ExpressionGenerator::new().generate_nix_module(&converted.variables);
+        assert!(generated.is_some());
+        assert_eq!(generated.unwrap(), EXPECTED);
+    }
+
+    const XRESOURCES: &str = "
+#include \"colors\"
+#define ACCENT #81a2be
+*.color4: ACCENT
+XTerm*faceSize: 11
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+xresources.properties = {
+\"*.color4\" = \"#81a2be\";
+\"XTerm*faceSize\" = 11;
+};
+}";
+}
@@ -1 +1,4 @@
+pub mod adapter;
+pub mod converter;
 pub mod parser;
+pub mod transform;
@@ -1,9 +1,60 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
 
-use clap::{arg, command, value_parser, ArgMatches};
-use nixify::parser::{
-    json::JsonParser, toml::TomlParser, yaml::YamlParser, ExpressionGenerator, ExpressionParser,
-    SupportedFormats,
+use clap::{arg, command, value_parser, ArgAction, ArgMatches, Command};
+use nixify::{
+    adapter::AdapterRegistry,
+    converter::{
+        compose::ComposeConverter,
+        cron::CronConverter,
+        crypttab::CrypttabConverter,
+        daemon::{DaemonConverter, SSHD},
+        firefox::FirefoxConverter,
+        fstab::FstabConverter,
+        git::GitConverter,
+        hosts::HostsConverter,
+        mimeapps::MimeAppsConverter,
+        packages::PackagesConverter,
+        shell::{Shell, ShellConverter},
+        ssh::SshConverter,
+        systemd::SystemdConverter,
+        vscode::VscodeConverter,
+        xresources::XresourcesConverter,
+        Converter, SupportedConverters, Target,
+    },
+    parser::{
+        columns::ColumnParser,
+        crontab::CrontabParser,
+        ini::IniParser,
+        json::JsonParser,
+        keyword::KeywordValueParser,
+        module::{SettingsFormat, TypedModule},
+        nix::NixParser,
+        packages::PackageListParser,
+        prefs::PrefsParser,
+        priority::{PathPriority, Priorities, Priority},
+        shell::ShellParser,
+        split::Split,
+        ssh::SshConfigParser,
+        systemd::SystemdUnitParser,
+        toml::TomlParser,
+        update::{differences, update},
+        xresources::XresourcesParser,
+        yaml::YamlParser,
+        ExpressionGenerator, ExpressionParser, NixVariable, SupportedFormats,
+    },
+    transform::{
+        builtin::BuiltinTransform,
+        executables::ExecutableDetection,
+        merge::{ListStrategy, Merge},
+        paths::PathDetection,
+        secrets::{secrets_file, Secret, SecretBackend, SecretScanner},
+        selector::{Exclude, Include, Selector},
+        Pipeline, Transform,
+    },
 };
 
 const VERSION: &str = "0.2.0";
@@ -21,7 +72,7 @@ fn main() {
         }
     }));
 
-    let matches = command!()
+    let command = command!()
         .name("nixify")
         .version(VERSION)
         .about("A CLI tool to turn existing configurations into nix syntax.")
@@ -29,7 +80,8 @@ fn main() {
             arg!(<FILE>)
                 .required(true)
                 .id("file")
-                .help("The file to convert.")
+                .num_args(1..)
+                .help("The file to convert. Several files or the files of a directory like conf.d, in lexical order, are merged, later files take precedence. Conflicting values are reported with both file names, not line numbers.")
                 .value_parser(value_parser!(std::path::PathBuf)),
         )
         .arg(
@@ -45,43 +97,736 @@ fn main() {
             arg!(--"name" <NAME>)
                 .short('n')
                 .long("name")
-                .required(true)
+                .required_unless_present("converter")
                 .id("name")
-                .help("The name of the program in the nix expression."),
+                .help("The name of the program in the nix expression, or the user running the jobs of a crontab."),
+        )
+        .arg(
+            arg!(--"converter" <CONVERTER>)
+                .short('c')
+                .long("converter")
+                .required(false)
+                .id("converter")
+                .help("Map the file onto the options of a specific nix module.")
+                .value_parser(value_parser!(SupportedConverters)),
+        )
+        .arg(
+            arg!(--"target" <TARGET>)
+                .short('t')
+                .long("target")
+                .required(false)
+                .id("target")
+                .default_value("home-manager")
+                .help("The configuration system to generate options for.")
+                .value_parser(value_parser!(Target)),
+        )
+        .arg(
+            arg!(--"package-map" <FILE>)
+                .long("package-map")
+                .required(false)
+                .id("package-map")
+                .help("A table of nixpkgs attributes and the names of the packages elsewhere, taking precedence over the bundled one (packages converter).")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--"merge-lists" <STRATEGY>)
+                .long("merge-lists")
+                .required(false)
+                .id("merge-lists")
+                .default_value("replace")
+                .help("How lists defined in several files are merged.")
+                .value_parser(value_parser!(ListStrategy)),
+        )
+        .arg(
+            arg!(--"include" <SELECTOR>)
+                .long("include")
+                .required(false)
+                .id("include")
+                .action(ArgAction::Append)
+                .help("Only convert the keys matched by the selector, e.g. `foo.*.name`, `list[2]` or `**.password`. Can be repeated.")
+                .value_parser(value_parser!(Selector)),
+        )
+        .arg(
+            arg!(--"exclude" <SELECTOR>)
+                .long("exclude")
+                .required(false)
+                .id("exclude")
+                .action(ArgAction::Append)
+                .help("Drop the keys matched by the selector. Can be repeated.")
+                .value_parser(value_parser!(Selector)),
+        )
+        .arg(
+            arg!(--"transform" <TRANSFORM>)
+                .long("transform")
+                .required(false)
+                .id("transform")
+                .action(ArgAction::Append)
+                .help("Transform the parsed file before generating the expression: drop-nulls, sort-keys, lowercase-keys, dedupe-lists or rename:PATTERN=REPLACEMENT. Can be repeated, transforms are applied in order after the selectors.")
+                .value_parser(value_parser!(BuiltinTransform)),
+        )
+        .arg(
+            arg!(--"detect-paths")
+                .long("detect-paths")
+                .id("detect-paths")
+                .help("Rewrite paths in the home directory to config.home.homeDirectory and the XDG directories of home-manager. Not supported with --target nixos."),
+        )
+        .arg(
+            arg!(--"dotfiles" <DIR>)
+                .long("dotfiles")
+                .required(false)
+                .id("dotfiles")
+                .help("The dotfiles repository the expression is written to, paths inside it become nix paths. Implies --detect-paths.")
+                .value_parser(value_parser!(std::path::PathBuf)),
         )
-        .get_matches();
-    handle_matches(matches);
+        .arg(
+            arg!(--"typed-module")
+                .long("typed-module")
+                .id("typed-module")
+                .conflicts_with("converter")
+                .help("Generate a module declaring programs.<NAME>.settings with types inferred from the file and its values as defaults, which writes the settings back to the file with pkgs.formats."),
+        )
+        .arg(
+            arg!(--"settings-format" <FORMAT>)
+                .long("settings-format")
+                .required(false)
+                .id("settings-format")
+                .help("The pkgs.formats format of the typed module, defaults to the format of the file or json.")
+                .value_parser(value_parser!(SettingsFormat)),
+        )
+        .arg(
+            arg!(--"priority" <PRIORITY>)
+                .long("priority")
+                .required(false)
+                .id("priority")
+                .conflicts_with("typed-module")
+                .help("Wrap the generated values in a priority, so they can be imported next to hand-written definitions: default (lib.mkDefault), force (lib.mkForce) or override:N (lib.mkOverride N).")
+                .value_parser(value_parser!(Priority)),
+        )
+        .arg(
+            arg!(--"priority-at" <PRIORITY>)
+                .long("priority-at")
+                .required(false)
+                .id("priority-at")
+                .action(ArgAction::Append)
+                .conflicts_with("typed-module")
+                .help("Wrap the values matched by a selector in a priority, written as SELECTOR=PRIORITY. Can be repeated, later ones take precedence over earlier ones and --priority.")
+                .value_parser(value_parser!(PathPriority)),
+        )
+        .arg(
+            arg!(--"priority-sets")
+                .long("priority-sets")
+                .id("priority-sets")
+                .help("Wrap top level values and matched attribute sets in their priority as a whole instead of every value they contain."),
+        )
+        .arg(
+            arg!(--"split" <DIR>)
+                .long("split")
+                .required(false)
+                .id("split")
+                .help("Write the expression to DIR split into a module per section, e.g. per key of programs.<NAME>.settings, and an entry module default.nix importing them.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--"split-lines" <LINES>)
+                .long("split-lines")
+                .required(false)
+                .id("split-lines")
+                .requires("split")
+                .help("Group sections into modules of up to LINES lines instead of writing a module per section.")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            arg!(--"update" <NIX>)
+                .long("update")
+                .required(false)
+                .id("update")
+                .conflicts_with("split")
+                .help("Update a nix file generated before in place instead of printing the expression. Only changed values are rewritten, comments, formatting and manual additions outside the generated options are kept.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--"detect-executables")
+                .long("detect-executables")
+                .id("detect-executables")
+                .help("Rewrite commands like /usr/bin/zsh or `rg --files` to the executables of their nixpkgs packages and report the ones that are not known."),
+        )
+        .arg(
+            arg!(--"replace-secrets" <BACKEND>)
+                .long("replace-secrets")
+                .required(false)
+                .id("replace-secrets")
+                .help("Replace values that look like secrets with the paths sops-nix or agenix decrypt them to and move them to the secrets file.")
+                .value_parser(value_parser!(SecretBackend)),
+        )
+        .arg(
+            arg!(--"secrets-file" <FILE>)
+                .long("secrets-file")
+                .required(false)
+                .id("secrets-file")
+                .default_value("secrets.yaml")
+                .help("The new file replaced secrets are written to, it is added to the .gitignore next to it. Existing files are not overwritten.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--"rules" <FILE>)
+                .long("rules")
+                .required(false)
+                .id("rules")
+                .help("A TOML or Nix file with rules mapping the configuration of programs onto nix options.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--"drop-volatile")
+                .long("drop-volatile")
+                .id("drop-volatile")
+                .help("Drop preferences the program keeps updating by itself, like timestamps (firefox converter)."),
+        )
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true);
+    // Checks take the same options as conversions, except those writing files
+    let check = Command::new("check")
+        .about("Compare the files to convert with the nix file generated from them and exit with an error if they differ, e.g. in a pre-commit hook.")
+        .arg(
+            arg!(<NIX>)
+                .required(true)
+                .id("nix")
+                .help("The nix file generated from the files.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .args(
+            command
+                .get_arguments()
+                .filter(|arg| {
+                    !["split", "split-lines", "update"].contains(&arg.get_id().as_str())
+                })
+                .cloned(),
+        );
+    let matches = command.subcommand(check).get_matches();
+    match matches.subcommand() {
+        Some(("check", matches)) => handle_matches(matches.to_owned()),
+        _ => handle_matches(matches),
+    }
 }
 
 fn handle_matches(matches: ArgMatches) {
     // Build a new ExpressionParser
     let expression_parser = ExpressionParser::new()
+        .add_parser(
+            SupportedFormats::fstab,
+            Box::new(ColumnParser::new(
+                &["device", "mountPoint", "fsType", "options", "dump", "pass"],
+                3,
+            )),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::hosts,
+            Box::new(ColumnParser::new(&["address", "hostnames"], 2).with_variadic_last_column()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::crypttab,
+            Box::new(ColumnParser::new(
+                &["name", "device", "password", "options"],
+                2,
+            )),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::xresources,
+            Box::new(XresourcesParser::new()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::package_list,
+            Box::new(PackageListParser::new()),
+        )
+        .unwrap()
+        .add_parser(SupportedFormats::shell, Box::new(ShellParser::new()))
+        .unwrap()
+        .add_parser(
+            SupportedFormats::keyword_value,
+            Box::new(KeywordValueParser::new()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::tmux_conf,
+            Box::new(KeywordValueParser::new().with_order_kept()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::sshd_config,
+            Box::new(KeywordValueParser::new().with_block_keywords(&["Match"])),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::system_crontab,
+            Box::new(CrontabParser::new().with_user_column()),
+        )
+        .unwrap()
+        .add_parser(SupportedFormats::crontab, Box::new(CrontabParser::new()))
+        .unwrap()
+        .add_parser(
+            SupportedFormats::mimeapps,
+            Box::new(SystemdUnitParser::new()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::systemd,
+            Box::new(SystemdUnitParser::new()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::ssh_config,
+            Box::new(SshConfigParser::new()),
+        )
+        .unwrap()
+        .add_parser(
+            SupportedFormats::firefox_prefs,
+            Box::new(PrefsParser::new()),
+        )
+        .unwrap()
+        .add_parser(SupportedFormats::ini, Box::new(IniParser::new()))
+        .unwrap()
         .add_parser(SupportedFormats::toml, Box::new(TomlParser::new()))
         .unwrap()
         .add_parser(SupportedFormats::yaml, Box::new(YamlParser::new()))
         .unwrap()
+        .add_parser(
+            SupportedFormats::jsonc,
+            Box::new(JsonParser::new().with_comments()),
+        )
+        .unwrap()
         .add_parser(SupportedFormats::json, Box::new(JsonParser::new()))
         .unwrap()
+        .add_parser(SupportedFormats::nix, Box::new(NixParser::new()))
+        .unwrap()
         .with_format_guessing();
-    let expression_generator = ExpressionGenerator::new().with_formatting();
+    let mut adapters = AdapterRegistry::builtin();
+    if let Some(rules) = matches.get_one::<PathBuf>("rules") {
+        let content = fs::read_to_string(rules).expect("Error reading given rules file");
+        adapters = adapters
+            .with_rules(&content)
+            .expect("Failed parsing the given rules file");
+    }
+    let mut priorities = Priorities::new();
+    if let Some(priority) = matches.get_one::<Priority>("priority") {
+        priorities = priorities.with_priority(*priority);
+    }
+    for path in matches
+        .get_many::<PathPriority>("priority-at")
+        .into_iter()
+        .flatten()
+    {
+        priorities = priorities.add_path(path.to_owned());
+    }
+    if matches.get_flag("priority-sets") {
+        priorities = priorities.with_whole_sets();
+    }
+    let expression_generator = ExpressionGenerator::new()
+        .with_formatting()
+        .with_adapters(adapters)
+        .with_priorities(priorities.clone());
 
     // Get arguments from clap
     let filepath: &PathBuf = matches.get_one("file").unwrap();
+    let files: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("file")
+        .unwrap()
+        .flat_map(|path| input_files(path))
+        .collect();
+    let converter: Option<Box<dyn Converter>> = matches
+        .get_one("converter")
+        .map(|c: &SupportedConverters| converter(c, &matches));
     let format: Option<SupportedFormats> = matches
         .get_one("format")
-        .map(|f: &SupportedFormats| f.to_owned());
-    let name: &String = matches.get_one("name").unwrap();
+        .map(|f: &SupportedFormats| f.to_owned())
+        .or(converter.as_ref().map(|c| c.format()));
+    // Converters fall back to the file name, e.g. for systemd units
+    let name: String = matches
+        .get_one("name")
+        .map(|n: &String| n.to_owned())
+        .or(filepath
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let target: &Target = matches.get_one("target").unwrap();
 
-    // Parse the file
-    let content = fs::read_to_string(filepath).expect("Error reading given file");
+    // Parse the files
     if format.is_none() {
         eprintln!("No format specified. Trying to guess the format..")
     }
-    let parsed = expression_parser
-        .parse(&content, &format)
-        .expect("Failed parsing the given file");
-    let expression = expression_generator
-        .generate_nix_expression(name, &parsed)
+    let sources: Vec<(String, Vec<NixVariable>)> = files
+        .iter()
+        .map(|file| {
+            let content = fs::read_to_string(file).expect("Error reading given file");
+            let parsed = expression_parser
+                .parse(&content, &format)
+                .expect("Failed parsing the given file");
+            (file.display().to_string(), parsed)
+        })
+        .collect();
+    let parsed = match sources.as_slice() {
+        [(_, parsed)] => parsed.to_owned(),
+        sources => {
+            let (merged, conflicts) = Merge::new()
+                .with_lists(*matches.get_one::<ListStrategy>("merge-lists").unwrap())
+                .merge(sources);
+            for conflict in conflicts {
+                eprintln!("Warning: {}", conflict);
+            }
+            merged
+        }
+    };
+    // Apply the selectors and transforms in the order they are listed
+    let mut pipeline = Pipeline::new();
+    if let Some(selectors) = matches.get_many::<Selector>("include") {
+        pipeline = pipeline.add_transform(Box::new(Include::new(
+            &selectors.cloned().collect::<Vec<Selector>>(),
+        )));
+    }
+    if let Some(selectors) = matches.get_many::<Selector>("exclude") {
+        pipeline = pipeline.add_transform(Box::new(Exclude::new(
+            &selectors.cloned().collect::<Vec<Selector>>(),
+        )));
+    }
+    for transform in matches
+        .get_many::<BuiltinTransform>("transform")
+        .into_iter()
+        .flatten()
+    {
+        pipeline = pipeline.add_transform(transform.transform());
+    }
+    if matches.get_flag("detect-paths") || matches.contains_id("dotfiles") {
+        let mut detection = PathDetection::new().with_target(*target);
+        if *target == Target::nixos {
+            eprintln!("Warning: NixOS has no home directory options, only paths in the dotfiles repository are rewritten");
+        }
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        if let Some(home) = &home {
+            detection = detection.with_home(home);
+        }
+        if let Some(dotfiles) = matches.get_one::<PathBuf>("dotfiles") {
+            detection = detection.with_dotfiles(&dotfiles_directory(dotfiles, home.as_deref()));
+        }
+        pipeline = pipeline.add_transform(Box::new(detection));
+    }
+    let parsed = pipeline
+        .apply(&parsed)
+        .expect("Failed transforming the given file");
+    let parsed = match matches.get_flag("detect-executables") {
+        true => {
+            let (detected, unresolved) = ExecutableDetection::new().detect(&parsed);
+            for command in unresolved {
+                eprintln!(
+                    "Warning: could not find a package providing `{}` of {}, it is kept as is",
+                    command.command,
+                    command.path.join(".")
+                );
+            }
+            detected
+        }
+        false => parsed,
+    };
+
+    // Keep secrets out of the nix store. Converters write values their own
+    // way, so their secrets are replaced in the converted options.
+    let scanner = SecretScanner::new().with_prefix(&name);
+    let backend = matches.get_one::<SecretBackend>("replace-secrets").copied();
+    let mut secrets = vec![];
+    let parsed = match backend {
+        Some(backend) if converter.is_none() => {
+            let (replaced, found) = scanner.replace(&parsed, backend);
+            secrets = found;
+            replaced
+        }
+        Some(_) => parsed,
+        None => {
+            for secret in scanner.scan(&parsed) {
+                eprintln!(
+                    "Warning: {} looks like a secret ({}) and will be readable by everyone in the nix store, use --replace-secrets to keep it out",
+                    secret.path.join("."),
+                    secret.reason
+                );
+            }
+            parsed
+        }
+    };
+    // Converters and typed modules generate full option paths
+    let module = match converter {
+        None if matches.get_flag("typed-module") => {
+            let settings_format = matches
+                .get_one::<SettingsFormat>("settings-format")
+                .copied()
+                .or(format.as_ref().and_then(SettingsFormat::from_format))
+                .unwrap_or(SettingsFormat::json);
+            let mut module = TypedModule::new(&name, settings_format).with_target(*target);
+            if let Some(file_name) = filepath.file_name() {
+                module = module.with_file(&format!("{}/{}", name, file_name.to_string_lossy()));
+            }
+            Some(module.generate(&parsed))
+        }
+        Some(converter) => {
+            let conversion = converter
+                .convert(&name, &parsed, target)
+                .expect("Failed converting the given file for the given target");
+            for warning in conversion.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            match backend {
+                Some(backend) => {
+                    let (replaced, found) = scanner.replace(&conversion.variables, backend);
+                    secrets = found;
+                    Some(replaced)
+                }
+                None => Some(conversion.variables),
+            }
+        }
+        None => None,
+    };
+    let declarations = match backend {
+        Some(backend) => SecretScanner::declarations(&secrets, backend),
+        None => vec![],
+    };
+    // Declaring secrets needs a module with option paths
+    let module = match module {
+        _ if declarations.is_empty() => module,
+        Some(values) => Some(values.into_iter().chain(declarations).collect()),
+        None => Some(
+            expression_generator
+                .module_values(&name, &parsed)
+                .into_iter()
+                .chain(declarations)
+                .collect(),
+        ),
+    };
+    // Priorities are applied while generating, report selectors matching
+    // nothing up front
+    priorities
+        .apply(
+            &module
+                .clone()
+                .unwrap_or_else(|| expression_generator.module_values(&name, &parsed)),
+        )
+        .expect("Failed applying the given priorities");
+    if let Ok(Some(directory)) = matches.try_get_one::<PathBuf>("split") {
+        let mut split = Split::new();
+        if let Some(lines) = matches.get_one::<usize>("split-lines") {
+            split = split.with_max_lines(*lines);
+        }
+        let files = match &module {
+            Some(values) => expression_generator.split_nix_module(values, &split),
+            None => expression_generator.split_nix_expression(&name, &parsed, &split),
+        }
         .unwrap();
+        write_secrets(
+            matches.get_one::<PathBuf>("secrets-file").unwrap(),
+            &secrets,
+        );
+        fs::create_dir_all(directory).expect("Error creating the output directory");
+        for (file, expression) in &files {
+            fs::write(directory.join(file), expression)
+                .expect("Error writing the generated modules");
+        }
+        eprintln!("Wrote {} modules to {}", files.len(), directory.display());
+        return;
+    }
+    if let Ok(Some(nix)) = matches.try_get_one::<PathBuf>("nix") {
+        check(
+            nix,
+            &module.unwrap_or_else(|| expression_generator.module_values(&name, &parsed)),
+            &expression_generator,
+        );
+        return;
+    }
+    if let Ok(Some(nix)) = matches.try_get_one::<PathBuf>("update") {
+        let content = fs::read_to_string(nix).expect("Error reading given nix file");
+        let generated = expression_generator
+            .generate_nix_module(
+                &module.unwrap_or_else(|| expression_generator.module_values(&name, &parsed)),
+            )
+            .unwrap();
+        let updated = update(&content, &generated).expect("Failed updating the given nix file");
+        write_secrets(
+            matches.get_one::<PathBuf>("secrets-file").unwrap(),
+            &secrets,
+        );
+        if updated == content {
+            eprintln!("{} is up to date", nix.display());
+        } else {
+            fs::write(nix, updated).expect("Error writing the updated nix file");
+            eprintln!("Updated {}", nix.display());
+        }
+        return;
+    }
+    let expression = match &module {
+        Some(values) => expression_generator.generate_nix_module(values),
+        None => expression_generator.generate_nix_expression(&name, &parsed),
+    }
+    .unwrap();
+    write_secrets(
+        matches.get_one::<PathBuf>("secrets-file").unwrap(),
+        &secrets,
+    );
     println!("{}", expression);
 }
+
+/// Compares a nix file with the module generated from the files to convert,
+/// both read with the nix parser so only their values are compared. Exits
+/// with an error if they differ.
+fn check(nix: &Path, values: &[NixVariable], expression_generator: &ExpressionGenerator) {
+    let fail = |error: &str| -> ! {
+        eprintln!("{}: {}", nix.display(), error);
+        std::process::exit(2);
+    };
+    let content = fs::read_to_string(nix).unwrap_or_else(|error| fail(&error.to_string()));
+    let generated = expression_generator.generate_nix_module(values).unwrap();
+    let differences = differences(&content, &generated)
+        .unwrap_or_else(|| fail("not a module defining an attribute set"));
+    if differences.is_empty() {
+        return;
+    }
+    println!(
+        "{} is out of date, {} values differ from the files it was generated from:",
+        nix.display(),
+        differences.len()
+    );
+    for difference in differences {
+        println!("{}", difference);
+    }
+    std::process::exit(1);
+}
+
+fn converter(converter_type: &SupportedConverters, matches: &ArgMatches) -> Box<dyn Converter> {
+    match converter_type {
+        SupportedConverters::bash => Box::new(ShellConverter::new(Shell::bash)),
+        SupportedConverters::compose => Box::new(ComposeConverter::new()),
+        SupportedConverters::crontab | SupportedConverters::cron_jobs => {
+            let mut converter = CronConverter::new();
+            if *converter_type == SupportedConverters::cron_jobs {
+                converter = converter.with_system_cron_jobs();
+            }
+            // NixOS runs every job as a user, per-user crontabs name none
+            let per_user = matches.get_one::<SupportedFormats>("format")
+                != Some(&SupportedFormats::system_crontab);
+            match matches.get_one::<String>("name") {
+                Some(user) => Box::new(converter.with_user(user)),
+                None if per_user && matches.get_one::<Target>("target") == Some(&Target::nixos) => {
+                    eprintln!("The jobs of a per-user crontab need a user on NixOS, pass it with --name USER");
+                    std::process::exit(1);
+                }
+                None => Box::new(converter),
+            }
+        }
+        SupportedConverters::crypttab => Box::new(CrypttabConverter::new()),
+        SupportedConverters::firefox => {
+            let converter = FirefoxConverter::new();
+            match matches.get_flag("drop-volatile") {
+                true => Box::new(converter.with_volatile_prefs_dropped()),
+                false => Box::new(converter),
+            }
+        }
+        SupportedConverters::fish => Box::new(ShellConverter::new(Shell::fish)),
+        SupportedConverters::fstab => Box::new(FstabConverter::new()),
+        SupportedConverters::git => Box::new(GitConverter::new()),
+        SupportedConverters::hosts => Box::new(HostsConverter::new()),
+        SupportedConverters::mimeapps => Box::new(MimeAppsConverter::new()),
+        SupportedConverters::packages => {
+            let converter = PackagesConverter::new();
+            match matches.get_one::<PathBuf>("package-map") {
+                Some(path) => Box::new(converter.with_overrides(
+                    &fs::read_to_string(path).expect("Error reading given package map"),
+                )),
+                None => Box::new(converter),
+            }
+        }
+        SupportedConverters::ssh => Box::new(SshConverter::new()),
+        SupportedConverters::sshd => Box::new(DaemonConverter::new(&SSHD)),
+        SupportedConverters::systemd => Box::new(SystemdConverter::new()),
+        SupportedConverters::vscode => Box::new(VscodeConverter::new()),
+        SupportedConverters::xresources => Box::new(XresourcesConverter::new()),
+        SupportedConverters::zsh => Box::new(ShellConverter::new(Shell::zsh)),
+    }
+}
+
+/// Returns the absolute path of the dotfiles repository, so it matches the
+/// absolute paths of the configuration. Inside the home directory it is
+/// relative to `$HOME` as written, even if that is a symbolic link.
+fn dotfiles_directory(dotfiles: &Path, home: Option<&Path>) -> PathBuf {
+    let dotfiles = fs::canonicalize(dotfiles).expect("Error reading given dotfiles directory");
+    home.and_then(|home| {
+        let rest = dotfiles.strip_prefix(fs::canonicalize(home).ok()?).ok()?;
+        Some(home.join(rest))
+    })
+    .unwrap_or(dotfiles)
+}
+
+/// Returns the files to convert for a FILE argument, the files of a
+/// directory in lexical order.
+fn input_files(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .expect("Error reading given directory")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Writes the plain text of replaced secrets to a new file only its owner can
+/// read and adds it to the .gitignore. Existing files are never overwritten,
+/// they may hold encrypted secrets.
+fn write_secrets(path: &Path, secrets: &[Secret]) {
+    if secrets.is_empty() {
+        return;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = match options.open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+            eprintln!(
+                "{} already exists, pass a new file with --secrets-file",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        Err(error) => panic!("Error writing the secrets file: {}", error),
+    };
+    file.write_all(secrets_file(secrets).as_bytes())
+        .expect("Error writing the secrets file");
+    ignore(path);
+    eprintln!(
+        "Moved {} secrets to {}, encrypt it before using the generated expression",
+        secrets.len(),
+        path.display()
+    );
+}
+
+/// Adds a file to the .gitignore in its directory, so it is not committed by
+/// accident.
+fn ignore(path: &Path) {
+    let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return;
+    };
+    let gitignore = path.with_file_name(".gitignore");
+    let content = fs::read_to_string(&gitignore).unwrap_or_default();
+    if content.lines().any(|line| line.trim() == file_name) {
+        return;
+    }
+    let separator = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(
+        &gitignore,
+        format!("{}{}{}\n", content, separator, file_name),
+    )
+    .expect("Error adding the secrets file to .gitignore");
+}
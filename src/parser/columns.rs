@@ -0,0 +1,137 @@
+use super::{NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for whitespace separated tables like `/etc/fstab`, `/etc/hosts` or
+/// `/etc/crypttab`.
+///
+/// Every line becomes an attribute set with the configured column names in the
+/// `rows` list. Trailing columns beyond the required ones are optional.
+pub struct ColumnParser {
+    columns: Vec<String>,
+    required: usize,
+    variadic: bool,
+}
+
+impl ColumnParser {
+    pub fn new(columns: &[&str], required: usize) -> ColumnParser {
+        ColumnParser {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            required,
+            variadic: false,
+        }
+    }
+
+    /// Collect all remaining fields into a list for the last column, like the
+    /// host names in `/etc/hosts`.
+    pub fn with_variadic_last_column(mut self) -> ColumnParser {
+        self.variadic = true;
+        self
+    }
+
+    fn parse_row(&self, line: &str) -> Option<NixVariableValue> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < self.required || (!self.variadic && fields.len() > self.columns.len()) {
+            return None;
+        }
+        let mut row = IndexMap::new();
+        for (index, column) in self.columns.iter().enumerate() {
+            if self.variadic && index == self.columns.len() - 1 {
+                row.insert(
+                    column.to_owned(),
+                    NixVariableValue::List(
+                        fields
+                            .iter()
+                            .skip(index)
+                            .map(|field| NixVariableValue::String(field.to_string()))
+                            .collect(),
+                    ),
+                );
+            } else if let Some(field) = fields.get(index) {
+                row.insert(
+                    column.to_owned(),
+                    NixVariableValue::String(field.to_string()),
+                );
+            }
+        }
+        Some(NixVariableValue::AttributeSet(row))
+    }
+}
+
+impl Parser for ColumnParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let rows = content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| self.parse_row(line))
+            .collect::<Option<Vec<NixVariableValue>>>()?;
+        Some(vec![NixVariable::new(
+            "rows",
+            &NixVariableValue::List(rows),
+        )])
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{columns::ColumnParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    fn string(s: &str) -> NixVariableValue {
+        NixVariableValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_columns() {
+        let parser = ColumnParser::new(&["device", "mountPoint", "type", "options"], 3);
+
+        let parsed = parser.parse("# comment\n/dev/sda1 / ext4 defaults\n\ntmpfs /tmp tmpfs\n");
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![NixVariable::new(
+                "rows",
+                &NixVariableValue::List(vec![
+                    NixVariableValue::AttributeSet(IndexMap::from([
+                        ("device".to_string(), string("/dev/sda1")),
+                        ("mountPoint".to_string(), string("/")),
+                        ("type".to_string(), string("ext4")),
+                        ("options".to_string(), string("defaults")),
+                    ])),
+                    NixVariableValue::AttributeSet(IndexMap::from([
+                        ("device".to_string(), string("tmpfs")),
+                        ("mountPoint".to_string(), string("/tmp")),
+                        ("type".to_string(), string("tmpfs")),
+                    ])),
+                ]),
+            )]
+        );
+        assert!(parser.parse("/dev/sda1 /").is_none());
+        assert!(parser.parse("a b c d e").is_none());
+    }
+
+    #[test]
+    fn test_variadic_columns() {
+        let parser = ColumnParser::new(&["address", "hostnames"], 2).with_variadic_last_column();
+
+        let parsed = parser.parse("10.0.0.1 nas nas.lan # storage");
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![NixVariable::new(
+                "rows",
+                &NixVariableValue::List(vec![NixVariableValue::AttributeSet(IndexMap::from([
+                    ("address".to_string(), string("10.0.0.1")),
+                    (
+                        "hostnames".to_string(),
+                        NixVariableValue::List(vec![string("nas"), string("nas.lan")]),
+                    ),
+                ]))]),
+            )]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use super::{NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for crontabs.
+///
+/// Environment assignments are collected into `environment`, every job
+/// becomes an attribute set with its `schedule` and `command` in the `jobs`
+/// list. System crontabs like `/etc/crontab` have an additional user column,
+/// which is returned as `user`.
+pub struct CrontabParser {
+    user_column: bool,
+}
+
+impl Default for CrontabParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrontabParser {
+    pub fn new() -> CrontabParser {
+        CrontabParser { user_column: false }
+    }
+
+    pub fn with_user_column(mut self) -> CrontabParser {
+        self.user_column = true;
+        self
+    }
+
+    /// Splits off the first `count` whitespace separated fields of a line.
+    fn split_fields(line: &str, count: usize) -> Option<(Vec<&str>, &str)> {
+        let mut fields = vec![];
+        let mut rest = line;
+        for _ in 0..count {
+            let (field, remainder) = rest.trim_start().split_once(char::is_whitespace)?;
+            fields.push(field);
+            rest = remainder;
+        }
+        Some((fields, rest.trim()))
+    }
+
+    fn parse_assignment(line: &str) -> Option<(String, String)> {
+        let (name, value) = line.split_once('=')?;
+        let name = name.trim();
+        let mut chars = name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(value);
+        Some((name.to_string(), value.to_string()))
+    }
+
+    fn parse_job(&self, line: &str) -> Option<NixVariableValue> {
+        let user_fields = usize::from(self.user_column);
+        let (schedule, fields, command) = if line.starts_with('@') {
+            let (fields, command) = CrontabParser::split_fields(line, 1 + user_fields)?;
+            (fields[0].to_string(), fields, command)
+        } else {
+            let (fields, command) = CrontabParser::split_fields(line, 5 + user_fields)?;
+            let schedule = &fields[..5];
+            if !schedule.iter().all(|field| {
+                field
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "*,-/".contains(c))
+            }) {
+                return None;
+            }
+            (schedule.join(" "), fields, command)
+        };
+        if command.is_empty() {
+            return None;
+        }
+        let mut job =
+            IndexMap::from([("schedule".to_string(), NixVariableValue::String(schedule))]);
+        if self.user_column {
+            job.insert(
+                "user".to_string(),
+                NixVariableValue::String(fields.last()?.to_string()),
+            );
+        }
+        job.insert(
+            "command".to_string(),
+            NixVariableValue::String(command.to_string()),
+        );
+        Some(NixVariableValue::AttributeSet(job))
+    }
+}
+
+impl Parser for CrontabParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut environment = IndexMap::new();
+        let mut jobs = vec![];
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match CrontabParser::parse_assignment(line) {
+                Some((name, value)) => {
+                    environment.insert(name, NixVariableValue::String(value));
+                }
+                None => jobs.push(self.parse_job(line)?),
+            }
+        }
+        Some(vec![
+            NixVariable::new("environment", &NixVariableValue::AttributeSet(environment)),
+            NixVariable::new("jobs", &NixVariableValue::List(jobs)),
+        ])
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{crontab::CrontabParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    fn job(schedule: &str, user: Option<&str>, command: &str) -> NixVariableValue {
+        let mut job = IndexMap::from([(
+            "schedule".to_string(),
+            NixVariableValue::String(schedule.to_string()),
+        )]);
+        if let Some(user) = user {
+            job.insert(
+                "user".to_string(),
+                NixVariableValue::String(user.to_string()),
+            );
+        }
+        job.insert(
+            "command".to_string(),
+            NixVariableValue::String(command.to_string()),
+        );
+        NixVariableValue::AttributeSet(job)
+    }
+
+    #[test]
+    fn test_crontab() {
+        let parsed = CrontabParser::new().parse(CRONTAB);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "environment",
+                    &NixVariableValue::AttributeSet(IndexMap::from([(
+                        "MAILTO".to_string(),
+                        NixVariableValue::String("admin@example.com".to_string()),
+                    )])),
+                ),
+                NixVariable::new(
+                    "jobs",
+                    &NixVariableValue::List(vec![
+                        job("*/15 9-17 * * mon-fri", None, "/usr/bin/sync-mail --quiet"),
+                        job("@daily", None, "backup.sh > /dev/null 2>&1"),
+                    ]),
+                ),
+            ]
+        );
+        assert!(CrontabParser::new().parse("foo: bar").is_none());
+    }
+
+    #[test]
+    fn test_system_crontab() {
+        let parsed = CrontabParser::new()
+            .with_user_column()
+            .parse("17 * * * * root cd / && run-parts --report /etc/cron.hourly");
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap()[1],
+            NixVariable::new(
+                "jobs",
+                &NixVariableValue::List(vec![job(
+                    "17 * * * *",
+                    Some("root"),
+                    "cd / && run-parts --report /etc/cron.hourly"
+                )]),
+            )
+        );
+    }
+
+    const CRONTAB: &str = "
+# m h dom mon dow command
+MAILTO=\"admin@example.com\"
+*/15 9-17 * * mon-fri /usr/bin/sync-mail --quiet
+@daily backup.sh > /dev/null 2>&1
+";
+}
@@ -0,0 +1,222 @@
+use super::{insert_or_append, NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for INI style files like `.gitconfig`.
+///
+/// Sections become attribute sets, git style subsections (`[section "sub"]`)
+/// become nested attribute sets and repeated keys are collected into lists.
+pub struct IniParser {}
+
+impl Default for IniParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IniParser {
+    pub fn new() -> IniParser {
+        IniParser {}
+    }
+
+    /// Splits a section header into the section and an optional subsection.
+    fn parse_section(header: &str) -> Option<Vec<String>> {
+        let header = header.strip_prefix('[')?.strip_suffix(']')?.trim();
+        match header.split_once(char::is_whitespace) {
+            Some((section, subsection)) => {
+                let subsection = subsection.trim();
+                let subsection = subsection.strip_prefix('"')?.strip_suffix('"')?;
+                Some(vec![section.to_string(), IniParser::unescape(subsection).0])
+            }
+            None if !header.is_empty() => Some(vec![header.to_string()]),
+            None => None,
+        }
+    }
+
+    /// Removes quotes, escapes and trailing comments from a raw value.
+    /// Returns the value and whether any part of it was quoted.
+    fn unescape(raw: &str) -> (String, bool) {
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut pending_whitespace = String::new();
+        let mut chars = raw.trim().chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    quoted = true;
+                    in_quotes = !in_quotes;
+                }
+                '\\' => {
+                    value.push_str(&pending_whitespace);
+                    pending_whitespace.clear();
+                    match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some('b') => {
+                            value.pop();
+                        }
+                        Some(escaped) => value.push(escaped),
+                        None => {}
+                    }
+                }
+                '#' | ';' if !in_quotes => break,
+                c if c.is_whitespace() && !in_quotes => pending_whitespace.push(c),
+                c => {
+                    value.push_str(&pending_whitespace);
+                    pending_whitespace.clear();
+                    value.push(c);
+                }
+            }
+        }
+        (value, quoted)
+    }
+
+    fn parse_value(raw: &str) -> NixVariableValue {
+        let (value, quoted) = IniParser::unescape(raw);
+        if quoted {
+            return NixVariableValue::String(value);
+        }
+        match value.to_lowercase().as_str() {
+            "true" | "yes" | "on" => NixVariableValue::Boolean(true),
+            "false" | "no" | "off" => NixVariableValue::Boolean(false),
+            _ => match value.parse::<i64>() {
+                Ok(i) => NixVariableValue::Number(i as f64),
+                Err(_) => NixVariableValue::String(value),
+            },
+        }
+    }
+
+    /// Returns the attribute set at the given path, creating it if needed.
+    fn section<'a>(
+        root: &'a mut IndexMap<String, NixVariableValue>,
+        path: &[String],
+    ) -> Option<&'a mut IndexMap<String, NixVariableValue>> {
+        path.iter().try_fold(root, |set, key| {
+            match set
+                .entry(key.to_owned())
+                .or_insert_with(|| NixVariableValue::AttributeSet(IndexMap::new()))
+            {
+                NixVariableValue::AttributeSet(set) => Some(set),
+                _ => None,
+            }
+        })
+    }
+}
+
+impl Parser for IniParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut root = IndexMap::new();
+        let mut path: Vec<String> = vec![];
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let mut line = line.trim().to_string();
+            // Join continuation lines
+            while line.ends_with('\\') && !line.ends_with("\\\\") {
+                line.pop();
+                line.push_str(lines.next().unwrap_or_default().trim());
+            }
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') {
+                path = IniParser::parse_section(&line)?;
+                IniParser::section(&mut root, &path)?;
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), IniParser::parse_value(value)),
+                // A key without a value is a boolean flag
+                None => (line.as_str(), NixVariableValue::Boolean(true)),
+            };
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+            insert_or_append(
+                IniParser::section(&mut root, &path)?,
+                key.to_string(),
+                value,
+            );
+        }
+        Some(
+            root.iter()
+                .map(|(name, value)| NixVariable::new(name, value))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{ini::IniParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+    use lazy_static::lazy_static;
+
+    #[test]
+    fn test_ini() {
+        let parser = IniParser::new();
+
+        let parsed = parser.parse(INI);
+        assert!(parsed.is_some());
+        assert_eq!(parsed.unwrap(), *EXPECTED)
+    }
+
+    #[test]
+    fn test_ini_invalid() {
+        let parser = IniParser::new();
+
+        assert!(parser.parse("foo:\n  bar: 1").is_none());
+        assert!(parser.parse("[foo \"bar]").is_none());
+    }
+
+    lazy_static! {
+        pub static ref EXPECTED: Vec<NixVariable> = vec![
+            NixVariable::new(
+                "user",
+                &NixVariableValue::AttributeSet(IndexMap::from([
+                    (
+                        "name".to_string(),
+                        NixVariableValue::String("Jane Doe".to_string()),
+                    ),
+                    ("signoff".to_string(), NixVariableValue::Boolean(true)),
+                ])),
+            ),
+            NixVariable::new(
+                "remote",
+                &NixVariableValue::AttributeSet(IndexMap::from([(
+                    "origin".to_string(),
+                    NixVariableValue::AttributeSet(IndexMap::from([
+                        (
+                            "fetch".to_string(),
+                            NixVariableValue::List(vec![
+                                NixVariableValue::String("+refs/heads/*".to_string()),
+                                NixVariableValue::String("+refs/tags/*".to_string()),
+                            ]),
+                        ),
+                        ("prune".to_string(), NixVariableValue::Boolean(false)),
+                        ("depth".to_string(), NixVariableValue::Number(3.0)),
+                    ])),
+                )])),
+            ),
+            NixVariable::new(
+                "alias",
+                &NixVariableValue::AttributeSet(IndexMap::from([(
+                    "lg".to_string(),
+                    NixVariableValue::String("log --format='%h; %s'".to_string()),
+                )])),
+            ),
+        ];
+    }
+    const INI: &str = "
+# A comment
+[user]
+    name = Jane Doe ; trailing comment
+    signoff
+[remote \"origin\"]
+    fetch = +refs/heads/*
+    fetch = +refs/tags/*
+    prune = false
+    depth = 3
+[alias]
+    lg = \"log --format='%h; %s'\"
+";
+}
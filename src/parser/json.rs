@@ -2,7 +2,11 @@ use super::{NixVariableValue, Parser};
 use crate::parser::NixVariable;
 use serde_json::Value;
 
-pub struct JsonParser {}
+/// Parser for JSON files. A top level array is returned as `items`.
+pub struct JsonParser {
+    comments: bool,
+}
+
 impl Default for JsonParser {
     fn default() -> Self {
         Self::new()
@@ -11,7 +15,56 @@ impl Default for JsonParser {
 
 impl JsonParser {
     pub fn new() -> JsonParser {
-        JsonParser {}
+        JsonParser { comments: false }
+    }
+
+    /// Accept JSON with comments and trailing commas (JSONC), as used by
+    /// VS Code.
+    pub fn with_comments(mut self) -> JsonParser {
+        self.comments = true;
+        self
+    }
+
+    /// Removes `//` and `/* */` comments and trailing commas.
+    fn strip_comments(content: &str) -> String {
+        let mut stripped = String::new();
+        let mut chars = content.chars().peekable();
+        let mut in_string = false;
+        while let Some(c) = chars.next() {
+            if in_string {
+                stripped.push(c);
+                match c {
+                    '\\' => stripped.extend(chars.next()),
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match (c, chars.peek()) {
+                ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+                ('/', Some('*')) => {
+                    chars.next();
+                    while let Some(c) = chars.next() {
+                        if c == '*' && chars.next_if_eq(&'/').is_some() {
+                            break;
+                        }
+                    }
+                }
+                ('}' | ']', _) => {
+                    // Drop a trailing comma in front of the closing bracket
+                    let trimmed = stripped.trim_end();
+                    if trimmed.ends_with(',') {
+                        stripped.truncate(trimmed.len() - 1);
+                    }
+                    stripped.push(c);
+                }
+                _ => {
+                    in_string = c == '"';
+                    stripped.push(c);
+                }
+            }
+        }
+        stripped
     }
 }
 
@@ -55,7 +108,13 @@ impl JsonParser {
 
 impl Parser for JsonParser {
     fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
-        let parsed = serde_json::from_str::<Value>(content).ok()?;
+        let parsed = match self.comments {
+            true => serde_json::from_str::<Value>(&JsonParser::strip_comments(content)).ok()?,
+            false => serde_json::from_str::<Value>(content).ok()?,
+        };
+        if parsed.is_array() {
+            return Some(vec![NixVariable::new("items", &self.parse_value(parsed))]);
+        }
         let parsed_object = parsed.as_object()?;
         Some(
             parsed_object
@@ -80,6 +139,28 @@ mod test {
         assert!(parsed.is_some());
         assert_eq!(parsed.unwrap(), *EXPECTED)
     }
+
+    #[test]
+    fn test_jsonc() {
+        let parser = JsonParser::new().with_comments();
+
+        let parsed = parser.parse(JSONC);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![NixVariable::new(
+                "items",
+                &NixVariableValue::List(vec![
+                    NixVariableValue::String("// not a comment".to_string()),
+                    NixVariableValue::AttributeSet(IndexMap::from([(
+                        "a".to_string(),
+                        NixVariableValue::String("\\\"/*".to_string()),
+                    )])),
+                ])
+            )]
+        );
+        assert!(JsonParser::new().parse(JSONC).is_none());
+    }
     lazy_static! {
         pub static ref EXPECTED: Vec<NixVariable> = vec![
             NixVariable::new(
@@ -110,6 +191,15 @@ mod test {
             ),
         ];
     }
+    const JSONC: &str = "
+// Place your key bindings in this file
+[
+    \"// not a comment\",
+    /* block
+       comment */
+    { \"a\": \"\\\\\\\"/*\", },
+]
+";
     const JSON: &str = "
 {
     \"foo\": {
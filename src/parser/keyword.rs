@@ -0,0 +1,168 @@
+use super::{insert_or_append, NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for `Keyword value` configuration files like `sshd_config`,
+/// `kitty.conf` or `tmux.conf`.
+///
+/// Keywords in front of the first block are returned as plain variables,
+/// every block starting with one of the block keywords becomes an attribute
+/// set named after its header, e.g. `Match User backup`. Repeated keywords are
+/// collected into lists, values are kept as strings.
+pub struct KeywordValueParser {
+    block_keywords: Vec<String>,
+    keep_order: bool,
+}
+
+impl Default for KeywordValueParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordValueParser {
+    pub fn new() -> KeywordValueParser {
+        KeywordValueParser {
+            block_keywords: vec![],
+            keep_order: false,
+        }
+    }
+
+    /// Returns every line in front of the first block as a variable of its
+    /// own in the order of the file, instead of collecting repeated keywords.
+    /// For files where the order matters, like `tmux.conf`.
+    pub fn with_order_kept(mut self) -> KeywordValueParser {
+        self.keep_order = true;
+        self
+    }
+
+    /// Keywords starting a new block, matched case-insensitively.
+    pub fn with_block_keywords(mut self, keywords: &[&str]) -> KeywordValueParser {
+        self.block_keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// Splits a line into keyword and argument. Both `Keyword value` and
+    /// `Keyword=value` are valid.
+    fn parse_line(line: &str) -> Option<(String, String)> {
+        let end = line.find(|c: char| c.is_whitespace() || c == '=')?;
+        let (keyword, rest) = line.split_at(end);
+        let rest = rest.trim_start();
+        let value = rest.strip_prefix('=').unwrap_or(rest).trim();
+        if !keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            || value.is_empty()
+        {
+            return None;
+        }
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Some((keyword.to_string(), value.to_string()))
+    }
+}
+
+impl Parser for KeywordValueParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut global = IndexMap::new();
+        let mut ordered = vec![];
+        let mut blocks: Vec<(String, IndexMap<String, NixVariableValue>)> = vec![];
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, value) = KeywordValueParser::parse_line(line)?;
+            match self
+                .block_keywords
+                .iter()
+                .find(|block| block.eq_ignore_ascii_case(&keyword))
+            {
+                Some(block) => blocks.push((format!("{} {}", block, value), IndexMap::new())),
+                None if self.keep_order && blocks.is_empty() => ordered.push(NixVariable {
+                    name: keyword,
+                    value: NixVariableValue::String(value),
+                }),
+                None => insert_or_append(
+                    blocks.last_mut().map_or(&mut global, |(_, block)| block),
+                    keyword,
+                    NixVariableValue::String(value),
+                ),
+            }
+        }
+        Some(
+            ordered
+                .into_iter()
+                .chain(
+                    global
+                        .into_iter()
+                        .map(|(name, value)| NixVariable { name, value }),
+                )
+                .chain(blocks.into_iter().map(|(name, block)| NixVariable {
+                    name,
+                    value: NixVariableValue::AttributeSet(block),
+                }))
+                .collect(),
+        )
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{keyword::KeywordValueParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    fn string(s: &str) -> NixVariableValue {
+        NixVariableValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_keyword_value() {
+        let parser = KeywordValueParser::new().with_block_keywords(&["Match"]);
+
+        let parsed = parser.parse(SSHD_CONFIG);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "Port",
+                    &NixVariableValue::List(vec![string("22"), string("2222")])
+                ),
+                NixVariable::new("PermitRootLogin", &string("no")),
+                NixVariable::new(
+                    "Match User backup",
+                    &NixVariableValue::AttributeSet(IndexMap::from([(
+                        "ForceCommand".to_string(),
+                        string("internal-sftp"),
+                    )])),
+                ),
+            ]
+        );
+        assert!(parser.parse("foo: bar").is_none());
+        assert_eq!(
+            KeywordValueParser::new()
+                .with_order_kept()
+                .parse("set -g mouse on\nbind r reload\nset -g mouse off")
+                .unwrap(),
+            vec![
+                NixVariable::new("set", &string("-g mouse on")),
+                NixVariable::new("bind", &string("r reload")),
+                NixVariable::new("set", &string("-g mouse off")),
+            ]
+        );
+    }
+
+    const SSHD_CONFIG: &str = "
+Port 22
+Port=2222
+PermitRootLogin no
+
+match User backup
+    ForceCommand internal-sftp
+";
+}
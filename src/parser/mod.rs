@@ -1,6 +1,24 @@
-use std::path::PathBuf;
+use crate::adapter::AdapterRegistry;
+use priority::Priorities;
+use split::Split;
+use std::{fmt, path::PathBuf};
+pub mod columns;
+pub mod crontab;
+pub mod ini;
 pub mod json;
+pub mod keyword;
+pub mod module;
+pub mod nix;
+pub mod packages;
+pub mod prefs;
+pub mod priority;
+pub mod shell;
+pub mod split;
+pub mod ssh;
+pub mod systemd;
 pub mod toml;
+pub mod update;
+pub mod xresources;
 pub mod yaml;
 
 use clap::ValueEnum;
@@ -12,10 +30,33 @@ pub enum SupportedFormats {
     yaml,
     toml,
     json,
+    jsonc,
+    ini,
+    ssh_config,
+    sshd_config,
+    keyword_value,
+    tmux_conf,
+    systemd,
+    crontab,
+    system_crontab,
+    fstab,
+    hosts,
+    crypttab,
+    firefox_prefs,
+    shell,
+    xresources,
+    mimeapps,
+    package_list,
+    nix,
 }
 
 pub trait Parser {
     fn parse(&self, content: &str) -> Option<Vec<NixVariable>>;
+    /// Whether the format is distinct enough to be tried when guessing the
+    /// format. Loose formats would accept almost any file.
+    fn guessable(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,10 +65,13 @@ pub struct NixVariable {
     pub value: NixVariableValue,
 }
 
-impl NixVariable {
-    pub fn to_string(&self) -> String {
-        format!("{} = {};\n", self.name, self.value.to_string())
+impl fmt::Display for NixVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} = {};", self.name, self.value)
     }
+}
+
+impl NixVariable {
     pub fn new(name: &str, value: &NixVariableValue) -> NixVariable {
         NixVariable {
             name: name.to_owned(),
@@ -36,6 +80,58 @@ impl NixVariable {
     }
 }
 
+/// Inserts a value, turning it into a list if the key is repeated.
+pub(crate) fn insert_or_append(
+    set: &mut IndexMap<String, NixVariableValue>,
+    key: String,
+    value: NixVariableValue,
+) {
+    match set.get_mut(&key) {
+        Some(NixVariableValue::List(list)) => list.push(value),
+        Some(existing) => {
+            *existing = NixVariableValue::List(vec![existing.to_owned(), value]);
+        }
+        None => {
+            set.insert(key, value);
+        }
+    }
+}
+
+/// Quotes an attribute name if it is not a valid nix identifier.
+pub fn attr_name(name: &str) -> String {
+    const KEYWORDS: [&str; 10] = [
+        "assert", "else", "if", "in", "inherit", "let", "or", "rec", "then", "with",
+    ];
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_-'".contains(c))
+        && !KEYWORDS.contains(&name);
+    if valid {
+        name.to_string()
+    } else {
+        quote_string(name)
+    }
+}
+
+/// Turns a string into a double quoted nix string literal.
+pub fn quote_string(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// Turns a multi-line string into an indented nix string literal.
+pub fn indented_string(s: &str) -> String {
+    let escaped = s.replace("''", "'''").replace("${", "''${");
+    format!("''\n{}\n''", escaped.trim_end_matches('\n'))
+}
 
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
@@ -47,29 +143,84 @@ pub enum NixVariableValue {
     Null,
     List(Vec<NixVariableValue>),
     AttributeSet(IndexMap<String, NixVariableValue>),
+    /// Application of a function like `lib.mkDefault` to its arguments.
+    Function(String, Vec<NixVariableValue>),
+    /// A multi-line string like a shell script, written as an indented string.
+    IndentedString(String),
+    /// A nix expression that is written as is, like `pkgs.ripgrep`.
+    Expression(String),
+    /// A comment, only valid as a list element.
+    Comment(String),
+    /// A value with the attributes of a scope in scope, like `with pkgs; [ ]`.
+    With(String, Box<NixVariableValue>),
 }
 
 impl NixVariableValue {
-    pub fn to_string(&self) -> String {
+    /// Wraps function applications in parentheses so they can be used as a
+    /// list element or function argument.
+    fn to_argument(&self) -> String {
+        match self {
+            Self::Function(_, _) | Self::With(_, _) => format!("({})", self),
+            Self::Expression(e) if e.contains(char::is_whitespace) && !e.starts_with('"') => {
+                format!("({})", self)
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Whether the value refers to functions from `lib`.
+    pub fn uses_lib(&self) -> bool {
+        match self {
+            Self::Function(name, args) => {
+                name.starts_with("lib.") || args.iter().any(|arg| arg.uses_lib())
+            }
+            Self::List(l) => l.iter().any(|value| value.uses_lib()),
+            Self::AttributeSet(a) => a.values().any(|value| value.uses_lib()),
+            Self::Expression(e) => e
+                .split(|c: char| !(c.is_ascii_alphanumeric() || "._-'".contains(c)))
+                .any(|word| word.starts_with("lib.")),
+            Self::With(scope, value) => scope.starts_with("lib") || value.uses_lib(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for NixVariableValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Number(n) => format!("{}", n),
-            Self::String(s) => format!("\"{}\"", s),
-            Self::Path(p) => p.to_str().expect("Error parsing file.").to_string(),
-            Self::Boolean(b) => format!("{}", b),
-            Self::Null => "null".to_string(),
-            Self::AttributeSet(a) => format!(
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write!(f, "{}", quote_string(s)),
+            Self::IndentedString(s) => write!(f, "{}", indented_string(s)),
+            Self::Expression(e) => write!(f, "{}", e),
+            Self::Comment(c) => write!(f, "# {}", c),
+            Self::With(scope, value) => write!(f, "with {}; {}", scope, value),
+            Self::Path(p) => write!(f, "{}", p.to_str().expect("Error parsing file.")),
+            Self::Boolean(b) => write!(f, "{}", b),
+            Self::Null => write!(f, "null"),
+            Self::AttributeSet(a) if a.is_empty() => write!(f, "{{ }}"),
+            Self::AttributeSet(a) => write!(
+                f,
                 "{{\n{}}}",
                 a.into_iter()
-                    .map(|(key, value)| NixVariable::new(key, value).to_string())
-                    .reduce(|acc, e| acc + &e)
-                    .expect("Error parsing file")
+                    .map(|(key, value)| NixVariable::new(&attr_name(key), value).to_string())
+                    .collect::<String>()
             ),
-            Self::List(l) => format!(
+            Self::List(l) if l.is_empty() => write!(f, "[ ]"),
+            Self::List(l) => write!(
+                f,
                 "[\n{}\n]",
                 l.iter()
-                    .map(|f| f.to_string())
-                    .reduce(|acc, e| acc + "\n" + &e)
-                    .unwrap()
+                    .map(|value| value.to_argument())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+            Self::Function(name, args) => write!(
+                f,
+                "{}",
+                std::iter::once(name.to_owned())
+                    .chain(args.iter().map(|arg| arg.to_argument()))
+                    .collect::<Vec<String>>()
+                    .join(" ")
             ),
         }
     }
@@ -119,10 +270,10 @@ impl ExpressionParser {
     ) -> Option<Vec<NixVariable>> {
         if format.is_none() && self.guess_format {
             self.parsers
-                .iter()
-                .map(|(_format, parser)| parser.parse(content))
-                .filter(|parsed| parsed.is_some())
-                .last()
+                .values()
+                .filter(|parser| parser.guessable())
+                .map(|parser| parser.parse(content))
+                .rfind(|parsed| parsed.is_some())
                 .flatten()
         } else if format.is_some() && self.parsers.contains_key(&format.unwrap()) {
             self.parsers[&format.unwrap()].parse(content)
@@ -134,6 +285,8 @@ impl ExpressionParser {
 
 pub struct ExpressionGenerator {
     formatting: bool,
+    adapters: AdapterRegistry,
+    priorities: Priorities,
 }
 
 impl Default for ExpressionGenerator {
@@ -144,15 +297,46 @@ impl Default for ExpressionGenerator {
 
 impl ExpressionGenerator {
     pub fn new() -> ExpressionGenerator {
-        ExpressionGenerator { formatting: false }
+        ExpressionGenerator {
+            formatting: false,
+            adapters: AdapterRegistry::new(),
+            priorities: Priorities::new(),
+        }
     }
     pub fn with_formatting(mut self) -> ExpressionGenerator {
         self.formatting = true;
         self
     }
+    /// Programs with an adapter are generated with the options of their
+    /// module instead of the parsed configuration as is.
+    pub fn with_adapters(mut self, adapters: AdapterRegistry) -> ExpressionGenerator {
+        self.adapters = adapters;
+        self
+    }
+    /// Wraps the generated values in `lib.mkDefault`, `lib.mkForce` or
+    /// `lib.mkOverride`. Nothing is generated if the selector of a priority
+    /// matches no value.
+    pub fn with_priorities(mut self, priorities: Priorities) -> ExpressionGenerator {
+        self.priorities = priorities;
+        self
+    }
+    /// The argument set of the generated module, `lib` is only requested when
+    /// it is used.
+    fn header(values: &[NixVariable]) -> String {
+        if values.iter().any(|v| v.value.uses_lib()) {
+            "{ config, lib, pkgs, ... }:\n".to_string()
+        } else {
+            "{ config, pkgs, ... }:\n".to_string()
+        }
+    }
+
     pub fn generate_nix_expression(&self, name: &str, values: &[NixVariable]) -> Option<String> {
+        if let Some(adapter) = self.adapters.get(name) {
+            return self.generate_nix_module(&adapter.adapt(values));
+        }
+        let values = &self.priorities.apply(values).ok()?;
         vec![
-            "{ config, pkgs, ... }:\n".to_string(),
+            ExpressionGenerator::header(values),
             "{\n".to_string(),
             format!("programs.{}.enable = true;\n", name),
         ]
@@ -168,13 +352,72 @@ impl ExpressionGenerator {
             }
         })
     }
+
+    /// Generates the expression split into several modules, see [`Split`].
+    /// Returns the file names with their content, the entry module first.
+    pub fn split_nix_expression(
+        &self,
+        name: &str,
+        values: &[NixVariable],
+        split: &Split,
+    ) -> Option<Vec<(String, String)>> {
+        self.split_nix_module(&self.module_values(name, values), split)
+    }
+
+    /// Returns the values of the expression as variables of a module, named
+    /// by their option paths.
+    pub fn module_values(&self, name: &str, values: &[NixVariable]) -> Vec<NixVariable> {
+        match self.adapters.get(name) {
+            Some(adapter) => adapter.adapt(values),
+            None => std::iter::once(crate::adapter::enable(name))
+                .chain(values.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// Generates a module from variables whose names are full option paths
+    /// split into several modules, see [`Split`].
+    pub fn split_nix_module(
+        &self,
+        values: &[NixVariable],
+        split: &Split,
+    ) -> Option<Vec<(String, String)>> {
+        // Selectors match in the whole module, not in the sections
+        let values = self.priorities.apply_paths(values).ok()?;
+        Some(
+            split
+                .split(&values)
+                .into_iter()
+                .map(|(file, values)| (file, self.module(&self.priorities.apply_default(&values))))
+                .collect(),
+        )
+    }
+
+    /// Generates a module from variables whose names are full option paths,
+    /// e.g. the output of a [`Converter`](crate::converter::Converter).
+    pub fn generate_nix_module(&self, values: &[NixVariable]) -> Option<String> {
+        Some(self.module(&self.priorities.apply(values).ok()?))
+    }
+
+    fn module(&self, values: &[NixVariable]) -> String {
+        let expression = vec![ExpressionGenerator::header(values), "{\n".to_string()]
+            .into_iter()
+            .chain(values.iter().map(|v| v.to_string()))
+            .chain(vec!["}".to_string()])
+            .collect::<String>();
+        if self.formatting {
+            nixpkgs_fmt::reformat_string(&expression)
+        } else {
+            expression
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        json::JsonParser, toml::TomlParser, yaml::YamlParser, ExpressionGenerator,
-        ExpressionParser, NixVariable, NixVariableValue,
+        columns::ColumnParser, json::JsonParser, toml::TomlParser, yaml::YamlParser,
+        ExpressionGenerator, ExpressionParser, NixVariable, NixVariableValue,
     };
     use indexmap::IndexMap;
     use lazy_static::lazy_static;
@@ -233,6 +476,75 @@ mod tests {
         assert_eq!(list.to_string(), "list = [\n4.2\n6.9\n];\n");
         assert_eq!(attrset.to_string(), "attrset = {\nfoo = \"bar\";\n};\n");
     }
+
+    #[test]
+    fn test_function_conversion() {
+        let function = NixVariable::new(
+            "function",
+            &NixVariableValue::List(vec![NixVariableValue::Function(
+                "lib.mkDefault".to_string(),
+                vec![NixVariableValue::String("foo\"${bar}".to_string())],
+            )]),
+        );
+        let quoted = NixVariable::new(
+            "quoted",
+            &NixVariableValue::AttributeSet(IndexMap::from([(
+                "foo.bar".to_string(),
+                NixVariableValue::AttributeSet(IndexMap::new()),
+            )])),
+        );
+
+        assert_eq!(
+            function.to_string(),
+            "function = [\n(lib.mkDefault \"foo\\\"\\${bar}\")\n];\n"
+        );
+        assert_eq!(quoted.to_string(), "quoted = {\n\"foo.bar\" = { };\n};\n");
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&[function]),
+            Some("{ config, lib, pkgs, ... }:\n{\nfunction = [\n(lib.mkDefault \"foo\\\"\\${bar}\")\n];\n}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expression_conversion() {
+        let packages = NixVariable::new(
+            "packages",
+            &NixVariableValue::With(
+                "pkgs".to_string(),
+                Box::new(NixVariableValue::List(vec![
+                    NixVariableValue::Expression("ripgrep".to_string()),
+                    NixVariableValue::Expression("lib.getExe fd".to_string()),
+                    NixVariableValue::Comment("glib.dev".to_string()),
+                ])),
+            ),
+        );
+
+        assert_eq!(
+            packages.to_string(),
+            "packages = with pkgs; [\nripgrep\n(lib.getExe fd)\n# glib.dev\n];\n"
+        );
+        assert!(packages.value.uses_lib());
+        assert!(!NixVariableValue::Expression("glib.dev".to_string()).uses_lib());
+    }
+
+    #[test]
+    fn test_format_guessing_skips_loose_formats() {
+        let parser = ExpressionParser::new()
+            .add_parser(
+                super::SupportedFormats::hosts,
+                Box::new(ColumnParser::new(&["address", "hostnames"], 2)),
+            )
+            .unwrap()
+            .add_parser(super::SupportedFormats::json, Box::new(JsonParser::new()))
+            .unwrap()
+            .with_format_guessing();
+
+        assert!(parser.parse("10.0.0.1 nas", &None).is_none());
+        assert!(parser
+            .parse("10.0.0.1 nas", &Some(super::SupportedFormats::hosts))
+            .is_some());
+    }
+
     #[test]
     fn test_format_guessing() {
         let parser = ExpressionParser::new()
@@ -0,0 +1,406 @@
+use super::{NixVariable, NixVariableValue, SupportedFormats};
+use crate::converter::Target;
+use clap::ValueEnum;
+use indexmap::IndexMap;
+
+/// The formats of `pkgs.formats` the settings of a typed module are written
+/// with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum SettingsFormat {
+    json,
+    toml,
+    yaml,
+    ini,
+}
+
+impl SettingsFormat {
+    /// The settings format matching the format of the parsed file, if there
+    /// is one.
+    pub fn from_format(format: &SupportedFormats) -> Option<SettingsFormat> {
+        match format {
+            SupportedFormats::json | SupportedFormats::jsonc => Some(SettingsFormat::json),
+            SupportedFormats::toml => Some(SettingsFormat::toml),
+            SupportedFormats::yaml => Some(SettingsFormat::yaml),
+            SupportedFormats::ini => Some(SettingsFormat::ini),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SettingsFormat::json => "json",
+            SettingsFormat::toml => "toml",
+            SettingsFormat::yaml => "yaml",
+            SettingsFormat::ini => "ini",
+        }
+    }
+}
+
+/// A type of `lib.types` inferred from a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionType {
+    Str,
+    Lines,
+    Int,
+    Float,
+    /// Both ints and floats, `lib.types.float` only accepts floats.
+    Number,
+    Bool,
+    Path,
+    Anything,
+    NullOr(Box<OptionType>),
+    ListOf(Box<OptionType>),
+    AttrsOf(Box<OptionType>),
+    /// A submodule declaring an option for every attribute.
+    Submodule(IndexMap<String, NixVariableValue>),
+}
+
+impl OptionType {
+    /// Infers the type of a value. Attribute sets become submodules with an
+    /// option per attribute, unless they are list elements.
+    pub fn infer(value: &NixVariableValue) -> OptionType {
+        match value {
+            NixVariableValue::AttributeSet(set) => OptionType::Submodule(set.to_owned()),
+            value => OptionType::infer_element(value),
+        }
+    }
+
+    /// Infers the type of a value whose attribute names are not known in
+    /// advance, like the elements of a list.
+    fn infer_element(value: &NixVariableValue) -> OptionType {
+        match value {
+            NixVariableValue::String(_) => OptionType::Str,
+            NixVariableValue::IndentedString(_) => OptionType::Lines,
+            NixVariableValue::Number(n) if n.fract() == 0.0 => OptionType::Int,
+            NixVariableValue::Number(_) => OptionType::Float,
+            NixVariableValue::Boolean(_) => OptionType::Bool,
+            NixVariableValue::Path(_) => OptionType::Path,
+            NixVariableValue::Null => OptionType::NullOr(Box::new(OptionType::Anything)),
+            NixVariableValue::List(list) => {
+                OptionType::ListOf(Box::new(OptionType::unify(list.iter())))
+            }
+            NixVariableValue::AttributeSet(set) => {
+                OptionType::AttrsOf(Box::new(OptionType::unify(set.values())))
+            }
+            _ => OptionType::Anything,
+        }
+    }
+
+    /// The narrowest type of all values, `null`s make it nullable.
+    fn unify<'a>(values: impl Iterator<Item = &'a NixVariableValue>) -> OptionType {
+        let mut nullable = false;
+        let mut types: Vec<OptionType> = vec![];
+        for value in values {
+            match value {
+                NixVariableValue::Null => nullable = true,
+                NixVariableValue::Comment(_) => (),
+                value => {
+                    let element = OptionType::infer_element(value);
+                    if !types.contains(&element) {
+                        types.push(element);
+                    }
+                }
+            }
+        }
+        let unified = match types.as_slice() {
+            [element] => element.to_owned(),
+            [OptionType::Int, OptionType::Float] | [OptionType::Float, OptionType::Int] => {
+                OptionType::Number
+            }
+            _ => OptionType::Anything,
+        };
+        match (nullable, unified) {
+            (true, OptionType::Anything) if types.is_empty() => {
+                OptionType::NullOr(Box::new(OptionType::Anything))
+            }
+            (true, unified @ OptionType::NullOr(_)) => unified,
+            (true, unified) => OptionType::NullOr(Box::new(unified)),
+            (false, unified) => unified,
+        }
+    }
+
+    fn to_value(&self) -> NixVariableValue {
+        let function = |name: &str, inner: &OptionType| {
+            NixVariableValue::Function(format!("lib.types.{}", name), vec![inner.to_value()])
+        };
+        match self {
+            OptionType::Str => NixVariableValue::Expression("lib.types.str".to_string()),
+            OptionType::Lines => NixVariableValue::Expression("lib.types.lines".to_string()),
+            OptionType::Int => NixVariableValue::Expression("lib.types.int".to_string()),
+            OptionType::Float => NixVariableValue::Expression("lib.types.float".to_string()),
+            OptionType::Number => NixVariableValue::Expression("lib.types.number".to_string()),
+            OptionType::Bool => NixVariableValue::Expression("lib.types.bool".to_string()),
+            OptionType::Path => NixVariableValue::Expression("lib.types.path".to_string()),
+            OptionType::Anything => NixVariableValue::Expression("lib.types.anything".to_string()),
+            OptionType::NullOr(inner) => function("nullOr", inner),
+            OptionType::ListOf(inner) => function("listOf", inner),
+            OptionType::AttrsOf(inner) => function("attrsOf", inner),
+            OptionType::Submodule(set) => NixVariableValue::Function(
+                "lib.types.submodule".to_string(),
+                vec![NixVariableValue::AttributeSet(IndexMap::from([(
+                    "options".to_string(),
+                    options(set),
+                )]))],
+            ),
+        }
+    }
+}
+
+/// Declares an option for a value, with the value as default. Submodules
+/// default to an empty set, the defaults of their options fill it.
+fn option(value: &NixVariableValue) -> NixVariableValue {
+    let option_type = OptionType::infer(value);
+    let default = match option_type {
+        OptionType::Submodule(_) => NixVariableValue::AttributeSet(IndexMap::new()),
+        _ => value.to_owned(),
+    };
+    NixVariableValue::Function(
+        "lib.mkOption".to_string(),
+        vec![NixVariableValue::AttributeSet(IndexMap::from([
+            ("type".to_string(), option_type.to_value()),
+            ("default".to_string(), default),
+        ]))],
+    )
+}
+
+/// Declares an option for every attribute of a set.
+fn options(set: &IndexMap<String, NixVariableValue>) -> NixVariableValue {
+    NixVariableValue::AttributeSet(
+        set.iter()
+            .filter(|(_, value)| !matches!(value, NixVariableValue::Comment(_)))
+            .map(|(key, value)| (key.to_owned(), option(value)))
+            .collect(),
+    )
+}
+
+/// Generates a reusable module for a program instead of setting its options:
+/// `programs.<name>.settings` is declared with the types inferred from the
+/// parsed configuration and its values as defaults, and the settings are
+/// written to the configuration file with `pkgs.formats`.
+pub struct TypedModule {
+    program: String,
+    format: SettingsFormat,
+    target: Target,
+    file: String,
+}
+
+impl TypedModule {
+    pub fn new(program: &str, format: SettingsFormat) -> TypedModule {
+        TypedModule {
+            program: program.to_string(),
+            format,
+            target: Target::home_manager,
+            file: format!("{}/config.{}", program, format.name()),
+        }
+    }
+
+    /// Writes the settings to `environment.etc` for NixOS instead of
+    /// `xdg.configFile`.
+    pub fn with_target(mut self, target: Target) -> TypedModule {
+        self.target = target;
+        self
+    }
+
+    /// The path of the configuration file, relative to the XDG configuration
+    /// directory or `/etc`.
+    pub fn with_file(mut self, file: &str) -> TypedModule {
+        self.file = file.to_string();
+        self
+    }
+
+    /// Returns the `options` and `config` of the module.
+    pub fn generate(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let settings_format =
+            NixVariableValue::Expression(format!("(pkgs.formats.{} {{ }})", self.format.name()));
+        let settings: IndexMap<String, NixVariableValue> = values
+            .iter()
+            .map(|variable| (variable.name.to_owned(), variable.value.to_owned()))
+            .collect();
+        let submodule = NixVariableValue::Function(
+            "lib.types.submodule".to_string(),
+            vec![NixVariableValue::AttributeSet(IndexMap::from([
+                (
+                    "freeformType".to_string(),
+                    NixVariableValue::Expression(format!("{}.type", settings_format)),
+                ),
+                ("options".to_string(), options(&settings)),
+            ]))],
+        );
+        let option = |option: &str| format!("config.programs.{}.{}", self.program, option);
+        let files = match self.target {
+            Target::home_manager => ["xdg", "configFile", &self.file, "source"],
+            Target::nixos => ["environment", "etc", &self.file, "source"],
+        };
+        vec![
+            NixVariable::new(
+                &format!("options.programs.{}", self.program),
+                &NixVariableValue::AttributeSet(IndexMap::from([
+                    (
+                        "enable".to_string(),
+                        NixVariableValue::Function(
+                            "lib.mkEnableOption".to_string(),
+                            vec![NixVariableValue::String(self.program.to_owned())],
+                        ),
+                    ),
+                    (
+                        "settings".to_string(),
+                        NixVariableValue::Function(
+                            "lib.mkOption".to_string(),
+                            vec![NixVariableValue::AttributeSet(IndexMap::from([
+                                ("type".to_string(), submodule),
+                                (
+                                    "default".to_string(),
+                                    NixVariableValue::AttributeSet(IndexMap::new()),
+                                ),
+                                (
+                                    "description".to_string(),
+                                    NixVariableValue::String(format!(
+                                        "Configuration of {}, written to {}.",
+                                        self.program, self.file
+                                    )),
+                                ),
+                            ]))],
+                        ),
+                    ),
+                ])),
+            ),
+            NixVariable::new(
+                "config",
+                &NixVariableValue::Function(
+                    "lib.mkIf".to_string(),
+                    vec![
+                        NixVariableValue::Expression(option("enable")),
+                        files.iter().rev().fold(
+                            NixVariableValue::Function(
+                                format!("{}.generate", settings_format),
+                                vec![
+                                    NixVariableValue::String(
+                                        self.file
+                                            .rsplit('/')
+                                            .next()
+                                            .unwrap_or(&self.file)
+                                            .to_string(),
+                                    ),
+                                    NixVariableValue::Expression(option("settings")),
+                                ],
+                            ),
+                            |value, key| {
+                                NixVariableValue::AttributeSet(IndexMap::from([(
+                                    key.to_string(),
+                                    value,
+                                )]))
+                            },
+                        ),
+                    ],
+                ),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        json::JsonParser,
+        module::{SettingsFormat, TypedModule},
+        ExpressionGenerator, Parser,
+    };
+
+    #[test]
+    fn test_typed_module() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let module = TypedModule::new("mytool", SettingsFormat::json).generate(&parsed);
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&module),
+            Some(EXPECTED.to_string())
+        );
+    }
+
+    const CONFIG: &str = "{
+    \"font\": { \"family\": \"Fira Code\", \"size\": 11.5 },
+    \"plugins\": [\"git\", null],
+    \"ports\": [8080, 8443],
+    \"profiles\": [{ \"name\": \"work\" }],
+    \"ratios\": [1, 1.5],
+    \"theme\": null,
+    \"verbose\": true
+}";
+
+    const EXPECTED: &str = "{ config, lib, pkgs, ... }:
+{
+options.programs.mytool = {
+enable = lib.mkEnableOption \"mytool\";
+settings = lib.mkOption {
+type = lib.types.submodule {
+freeformType = (pkgs.formats.json { }).type;
+options = {
+font = lib.mkOption {
+type = lib.types.submodule {
+options = {
+family = lib.mkOption {
+type = lib.types.str;
+default = \"Fira Code\";
+};
+size = lib.mkOption {
+type = lib.types.float;
+default = 11.5;
+};
+};
+};
+default = { };
+};
+plugins = lib.mkOption {
+type = lib.types.listOf (lib.types.nullOr lib.types.str);
+default = [
+\"git\"
+null
+];
+};
+ports = lib.mkOption {
+type = lib.types.listOf lib.types.int;
+default = [
+8080
+8443
+];
+};
+profiles = lib.mkOption {
+type = lib.types.listOf (lib.types.attrsOf lib.types.str);
+default = [
+{
+name = \"work\";
+}
+];
+};
+ratios = lib.mkOption {
+type = lib.types.listOf lib.types.number;
+default = [
+1
+1.5
+];
+};
+theme = lib.mkOption {
+type = lib.types.nullOr lib.types.anything;
+default = null;
+};
+verbose = lib.mkOption {
+type = lib.types.bool;
+default = true;
+};
+};
+};
+default = { };
+description = \"Configuration of mytool, written to mytool/config.json.\";
+};
+};
+config = lib.mkIf config.programs.mytool.enable {
+xdg = {
+configFile = {
+\"mytool/config.json\" = {
+source = (pkgs.formats.json { }).generate \"config.json\" config.programs.mytool.settings;
+};
+};
+};
+};
+}";
+}
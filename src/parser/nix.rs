@@ -0,0 +1,265 @@
+use super::{NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+use rnix::{
+    types::{
+        AttrSet, EntryHolder, Ident, Lambda, List, ParsedType, Str, TokenWrapper, TypedNode,
+        UnaryOp, UnaryOpKind, Value, Wrapper,
+    },
+    NixValue, StrPart, SyntaxNode,
+};
+use std::path::PathBuf;
+
+/// Parser for Nix expressions, e.g. rules files or existing modules.
+///
+/// The top level has to be an attribute set, the argument set of a module is
+/// skipped. Attribute sets, lists, strings, numbers, paths, booleans and null
+/// are read as values and dotted keys are nested. Any other expression is
+/// kept as written.
+pub struct NixParser {}
+
+impl Default for NixParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NixParser {
+    pub fn new() -> NixParser {
+        NixParser {}
+    }
+
+    /// Returns the name of a key segment, which is an identifier or a string.
+    pub(crate) fn key(node: SyntaxNode) -> Option<String> {
+        match ParsedType::cast(node)? {
+            ParsedType::Ident(ident) => Some(ident.as_str().to_string()),
+            ParsedType::Str(string) => NixParser::literal(&string),
+            _ => None,
+        }
+    }
+
+    /// Returns the content of a string without interpolations.
+    fn literal(string: &Str) -> Option<String> {
+        string
+            .parts()
+            .into_iter()
+            .map(|part| match part {
+                StrPart::Literal(literal) => Some(literal),
+                StrPart::Ast(_) => None,
+            })
+            .collect()
+    }
+
+    fn attribute_set(set: &AttrSet) -> Option<IndexMap<String, NixVariableValue>> {
+        if set.recursive() {
+            return None;
+        }
+        let mut values = IndexMap::new();
+        for inherit in set.inherits() {
+            for ident in inherit.idents() {
+                let name = ident.as_str().to_string();
+                let source = match inherit.from().and_then(|from| from.inner()) {
+                    Some(from) => format!("{}.{}", from.text(), name),
+                    None => name.to_owned(),
+                };
+                values.insert(name, NixVariableValue::Expression(source));
+            }
+        }
+        for entry in set.entries() {
+            let path = entry
+                .key()?
+                .path()
+                .map(NixParser::key)
+                .collect::<Option<Vec<String>>>()?;
+            NixParser::insert(&mut values, &path, NixParser::value(entry.value()?)?)?;
+        }
+        Some(values)
+    }
+
+    /// Inserts a value at a dotted path, merging it with sets defined before.
+    fn insert(
+        set: &mut IndexMap<String, NixVariableValue>,
+        path: &[String],
+        value: NixVariableValue,
+    ) -> Option<()> {
+        let (key, rest) = path.split_first()?;
+        if rest.is_empty() {
+            match (set.get_mut(key), value) {
+                (
+                    Some(NixVariableValue::AttributeSet(existing)),
+                    NixVariableValue::AttributeSet(new),
+                ) => {
+                    for (k, v) in new {
+                        NixParser::insert(existing, &[k], v)?;
+                    }
+                }
+                (Some(_), _) => return None,
+                (None, value) => {
+                    set.insert(key.to_owned(), value);
+                }
+            }
+            return Some(());
+        }
+        match set
+            .entry(key.to_owned())
+            .or_insert_with(|| NixVariableValue::AttributeSet(IndexMap::new()))
+        {
+            NixVariableValue::AttributeSet(nested) => NixParser::insert(nested, rest, value),
+            _ => None,
+        }
+    }
+
+    fn value(node: SyntaxNode) -> Option<NixVariableValue> {
+        let expression = NixVariableValue::Expression(node.text().to_string());
+        Some(match ParsedType::cast(node)? {
+            ParsedType::AttrSet(set) => match NixParser::attribute_set(&set) {
+                Some(values) => NixVariableValue::AttributeSet(values),
+                None => expression,
+            },
+            ParsedType::List(list) => NixParser::list(&list)?,
+            ParsedType::Str(string) => match NixParser::literal(&string) {
+                Some(s) if string.node().text().to_string().starts_with("''") => {
+                    NixVariableValue::IndentedString(s)
+                }
+                Some(s) => NixVariableValue::String(s),
+                None => expression,
+            },
+            ParsedType::Value(value) => NixParser::literal_value(&value)?,
+            ParsedType::Ident(ident) => NixParser::ident(&ident),
+            ParsedType::UnaryOp(op) => NixParser::negation(&op).unwrap_or(expression),
+            ParsedType::Paren(paren) => NixParser::value(paren.inner()?)?,
+            ParsedType::Error(_) => return None,
+            _ => expression,
+        })
+    }
+
+    fn list(list: &List) -> Option<NixVariableValue> {
+        list.items()
+            .map(NixParser::value)
+            .collect::<Option<Vec<NixVariableValue>>>()
+            .map(NixVariableValue::List)
+    }
+
+    fn literal_value(value: &Value) -> Option<NixVariableValue> {
+        Some(match value.to_value().ok()? {
+            NixValue::Integer(i) => NixVariableValue::Number(i as f64),
+            NixValue::Float(f) => NixVariableValue::Number(f),
+            NixValue::String(s) => NixVariableValue::String(s),
+            NixValue::Path(..) => NixVariableValue::Path(Box::new(PathBuf::from(value.as_str()))),
+        })
+    }
+
+    fn ident(ident: &Ident) -> NixVariableValue {
+        match ident.as_str() {
+            "true" => NixVariableValue::Boolean(true),
+            "false" => NixVariableValue::Boolean(false),
+            "null" => NixVariableValue::Null,
+            name => NixVariableValue::Expression(name.to_string()),
+        }
+    }
+
+    fn negation(op: &UnaryOp) -> Option<NixVariableValue> {
+        if op.operator() != UnaryOpKind::Negate {
+            return None;
+        }
+        match NixParser::literal_value(&Value::cast(op.value()?)?)? {
+            NixVariableValue::Number(n) => Some(NixVariableValue::Number(-n)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the top level attribute set of a nix file.
+pub(crate) fn module_set(content: &str) -> Option<AttrSet> {
+    let ast = rnix::parse(content);
+    if !ast.errors().is_empty() {
+        return None;
+    }
+    let mut node = ast.root().inner()?;
+    // Skip the argument set of modules, e.g. `{ config, pkgs, ... }:`
+    while let Some(lambda) = Lambda::cast(node.clone()) {
+        node = lambda.body()?;
+    }
+    AttrSet::cast(node)
+}
+
+impl Parser for NixParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        Some(
+            NixParser::attribute_set(&module_set(content)?)?
+                .into_iter()
+                .map(|(name, value)| NixVariable { name, value })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{nix::NixParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_nix() {
+        let parser = NixParser::new();
+
+        let parsed = parser.parse(MODULE);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![NixVariable::new(
+                "programs",
+                &NixVariableValue::AttributeSet(IndexMap::from([(
+                    "git".to_string(),
+                    NixVariableValue::AttributeSet(IndexMap::from([
+                        ("enable".to_string(), NixVariableValue::Boolean(true)),
+                        (
+                            "userName".to_string(),
+                            NixVariableValue::String("Jane Doe".to_string())
+                        ),
+                        (
+                            "extraConfig".to_string(),
+                            NixVariableValue::AttributeSet(IndexMap::from([
+                                (
+                                    "core.pager".to_string(),
+                                    NixVariableValue::Expression(
+                                        "lib.getExe pkgs.delta".to_string()
+                                    )
+                                ),
+                                (
+                                    "pull".to_string(),
+                                    NixVariableValue::AttributeSet(IndexMap::from([(
+                                        "rebase".to_string(),
+                                        NixVariableValue::Null
+                                    )]))
+                                ),
+                                (
+                                    "offset".to_string(),
+                                    NixVariableValue::List(vec![
+                                        NixVariableValue::Number(-1.0),
+                                        NixVariableValue::Number(0.5)
+                                    ])
+                                ),
+                            ]))
+                        ),
+                    ]))
+                )]))
+            )]
+        );
+        assert!(parser.parse("{ foo = ; }").is_none());
+        assert!(parser.parse("[ 1 2 ]").is_none());
+    }
+
+    const MODULE: &str = "
+{ config, lib, pkgs, ... }:
+{
+  # Version control
+  programs.git.enable = true;
+  programs.git = {
+    userName = \"Jane Doe\";
+    extraConfig.\"core.pager\" = lib.getExe pkgs.delta;
+    extraConfig.pull.rebase = null;
+    extraConfig.offset = [ (-1) 0.5 ];
+  };
+}
+";
+}
@@ -0,0 +1,143 @@
+use super::{NixVariable, NixVariableValue, Parser};
+
+/// Parser for lists of installed packages.
+///
+/// The output of `apt list --installed`, `pacman -Qqe`, `brew leaves`,
+/// `pip freeze` and `npm ls -g` is detected line by line. Package names are
+/// collected by ecosystem into `system`, `python` and `node`, versions are
+/// dropped.
+pub struct PackageListParser {}
+
+impl Default for PackageListParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PackageListParser {
+    pub fn new() -> PackageListParser {
+        PackageListParser {}
+    }
+
+    /// Returns the ecosystem and name of the package on a line.
+    fn parse_line(line: &str) -> Option<(&'static str, String)> {
+        // npm ls -g prints a tree, e.g. `├── typescript@5.2.2`
+        if let Some(package) = ["├── ", "└── ", "+-- ", "`-- "]
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))
+        {
+            let name = match package.rsplit_once('@') {
+                Some((name, _)) if !name.is_empty() => name,
+                _ => package,
+            };
+            return Some(("node", name.to_string()));
+        }
+        // apt list prints `name/suite,now version arch [installed]`
+        if line.contains("[installed") {
+            return Some(("system", line.split_once('/')?.0.to_string()));
+        }
+        // pip freeze prints requirements, e.g. `requests==2.31.0` or `foo @ file:///tmp/foo`
+        if let Some(index) = line.find(['=', '<', '>', '~', '@', '!']) {
+            let name = line[..index].trim();
+            if !name.contains(char::is_whitespace) {
+                return Some(("python", name.to_string()));
+            }
+        }
+        // pacman and brew print one name per line, pacman -Qe with its version
+        let name = line.split_whitespace().next()?;
+        Some(("system", name.rsplit('/').next()?.to_string()))
+    }
+}
+
+impl Parser for PackageListParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut packages: Vec<(&str, Vec<NixVariableValue>)> =
+            vec![("system", vec![]), ("python", vec![]), ("node", vec![])];
+        for line in content.lines().map(str::trim) {
+            // Skip headers like `Listing...` of apt or the prefix printed by npm
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with('-')
+                || line.starts_with("Listing")
+                || (line.starts_with('/') && !line.contains(char::is_whitespace))
+            {
+                continue;
+            }
+            let (ecosystem, name) = PackageListParser::parse_line(line)?;
+            if !name.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '@')
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._+-@/".contains(c))
+            {
+                return None;
+            }
+            let (_, names) = packages.iter_mut().find(|(e, _)| *e == ecosystem)?;
+            let name = NixVariableValue::String(name);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        Some(
+            packages
+                .into_iter()
+                .map(|(ecosystem, names)| {
+                    NixVariable::new(ecosystem, &NixVariableValue::List(names))
+                })
+                .collect(),
+        )
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{packages::PackageListParser, NixVariable, NixVariableValue, Parser};
+
+    fn names(names: &[&str]) -> NixVariableValue {
+        NixVariableValue::List(
+            names
+                .iter()
+                .map(|name| NixVariableValue::String(name.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_package_list() {
+        let parser = PackageListParser::new();
+
+        let parsed = parser.parse(PACKAGES);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "system",
+                    &names(&["fd-find", "ripgrep", "neovim", "gh", "jq"])
+                ),
+                NixVariable::new("python", &names(&["requests", "black", "mylib"])),
+                NixVariable::new("node", &names(&["@angular/cli", "typescript"])),
+            ]
+        );
+        assert!(parser.parse("{ \"foo\": \"bar\" }").is_none());
+    }
+
+    const PACKAGES: &str = "
+Listing... Done
+fd-find/jammy,now 8.3.1-1ubuntu0.1 amd64 [installed]
+ripgrep/jammy,now 13.0.0-2ubuntu0.1 amd64 [installed,automatic]
+neovim 0.9.5-1
+gh
+homebrew/core/jq
+requests==2.31.0
+black>=23.0
+mylib @ file:///home/user/src/mylib
+-e git+https://github.com/user/tool.git#egg=tool
+/usr/lib
+├── @angular/cli@17.0.0
+└── typescript@5.2.2
+";
+}
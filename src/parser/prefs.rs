@@ -0,0 +1,153 @@
+use super::{NixVariable, NixVariableValue, Parser};
+
+/// Parser for Firefox preference files (`prefs.js` and `user.js`).
+///
+/// Every `user_pref("name", value);` call becomes a variable named after the
+/// preference. Values are JavaScript string, integer or boolean literals.
+pub struct PrefsParser {}
+
+impl Default for PrefsParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrefsParser {
+    pub fn new() -> PrefsParser {
+        PrefsParser {}
+    }
+
+    /// Skips whitespace and `//`, `#` and `/* */` comments.
+    fn skip(mut rest: &str) -> Option<&str> {
+        loop {
+            rest = rest.trim_start();
+            if rest.starts_with("//") || rest.starts_with('#') {
+                rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                rest = comment.split_once("*/")?.1;
+            } else {
+                return Some(rest);
+            }
+        }
+    }
+
+    fn expect<'a>(rest: &'a str, token: &str) -> Option<&'a str> {
+        PrefsParser::skip(rest)?.strip_prefix(token)
+    }
+
+    /// Parses a single or double quoted string literal.
+    fn parse_string(rest: &str) -> Option<(String, &str)> {
+        let rest = PrefsParser::skip(rest)?;
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let mut value = String::new();
+        let mut chars = rest[1..].char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                c if c == quote => return Some((value, &rest[index + 2..])),
+                '\\' => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    escape @ ('u' | 'x') => {
+                        let digits: String = chars
+                            .by_ref()
+                            .take(if escape == 'u' { 4 } else { 2 })
+                            .map(|(_, c)| c)
+                            .collect();
+                        value.push(char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?);
+                    }
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+        None
+    }
+
+    fn parse_value(rest: &str) -> Option<(NixVariableValue, &str)> {
+        let rest = PrefsParser::skip(rest)?;
+        if rest.starts_with(['"', '\'']) {
+            let (value, rest) = PrefsParser::parse_string(rest)?;
+            return Some((NixVariableValue::String(value), rest));
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '.'))
+            .unwrap_or(rest.len());
+        let value = match &rest[..end] {
+            "true" => NixVariableValue::Boolean(true),
+            "false" => NixVariableValue::Boolean(false),
+            number => NixVariableValue::Number(number.parse::<i64>().ok()? as f64),
+        };
+        Some((value, &rest[end..]))
+    }
+
+    /// Parses a `user_pref("name", value);` call.
+    fn parse_pref(rest: &str) -> Option<(NixVariable, &str)> {
+        let rest = ["user_pref", "pref", "lockPref", "sticky_pref"]
+            .iter()
+            .find_map(|function| rest.strip_prefix(function))?;
+        let rest = PrefsParser::expect(rest, "(")?;
+        let (name, rest) = PrefsParser::parse_string(rest)?;
+        let rest = PrefsParser::expect(rest, ",")?;
+        let (value, rest) = PrefsParser::parse_value(rest)?;
+        let rest = PrefsParser::expect(rest, ")")?;
+        let rest = PrefsParser::expect(rest, ";")?;
+        Some((NixVariable { name, value }, rest))
+    }
+}
+
+impl Parser for PrefsParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut prefs = vec![];
+        let mut rest = PrefsParser::skip(content)?;
+        while !rest.is_empty() {
+            let (pref, remainder) = PrefsParser::parse_pref(rest)?;
+            prefs.push(pref);
+            rest = PrefsParser::skip(remainder)?;
+        }
+        Some(prefs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{prefs::PrefsParser, NixVariable, NixVariableValue, Parser};
+
+    #[test]
+    fn test_prefs() {
+        let parser = PrefsParser::new();
+
+        let parsed = parser.parse(PREFS);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "browser.startup.homepage",
+                    &NixVariableValue::String("https://example.com/?q=\"nix\"".to_string())
+                ),
+                NixVariable::new("browser.startup.page", &NixVariableValue::Number(3.0)),
+                NixVariable::new("signon.rememberSignons", &NixVariableValue::Boolean(false)),
+                NixVariable::new(
+                    "intl.accept_languages",
+                    &NixVariableValue::String("de-DE, en\u{e9}".to_string())
+                ),
+            ]
+        );
+        assert!(parser.parse("user_pref(\"a\", b);").is_none());
+        assert!(parser.parse("foo = bar").is_none());
+    }
+
+    const PREFS: &str = "// Mozilla User Preferences
+
+/* Do not edit this file.
+ * If you make changes to this file while the application is running,
+ * the changes will be overwritten when the application exits.
+ */
+
+user_pref(\"browser.startup.homepage\", \"https://example.com/?q=\\\"nix\\\"\");
+user_pref(\"browser.startup.page\", 3);
+user_pref('signon.rememberSignons', false); // passwords live elsewhere
+user_pref(\"intl.accept_languages\", \"de-DE, en\\u00e9\");
+";
+}
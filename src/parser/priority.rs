@@ -0,0 +1,314 @@
+use super::{NixVariable, NixVariableValue};
+use crate::transform::selector::Selector;
+use std::str::FromStr;
+
+/// Functions of `lib` setting the priority of a definition.
+const PRIORITY_FUNCTIONS: [&str; 4] = [
+    "lib.mkDefault",
+    "lib.mkForce",
+    "lib.mkOverride",
+    "lib.mkOptionDefault",
+];
+
+/// The priority of generated definitions, so they can be imported next to
+/// hand-written ones defining the same options.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Priority {
+    /// `lib.mkDefault`, hand-written definitions win.
+    Default,
+    /// `lib.mkForce`, the generated definitions win.
+    Force,
+    /// `lib.mkOverride N`, lower numbers win.
+    Override(u32),
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Priority::Default),
+            "force" => Ok(Priority::Force),
+            s => s
+                .strip_prefix("override:")
+                .and_then(|priority| priority.parse().ok())
+                .map(Priority::Override)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown priority `{}`, expected default, force or override:N",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+impl Priority {
+    fn wrap(&self, value: NixVariableValue) -> NixVariableValue {
+        match self {
+            Priority::Default => {
+                NixVariableValue::Function("lib.mkDefault".to_string(), vec![value])
+            }
+            Priority::Force => NixVariableValue::Function("lib.mkForce".to_string(), vec![value]),
+            Priority::Override(priority) => NixVariableValue::Function(
+                "lib.mkOverride".to_string(),
+                vec![NixVariableValue::Number(*priority as f64), value],
+            ),
+        }
+    }
+}
+
+/// A priority for the values matched by a selector, written as
+/// `SELECTOR=PRIORITY`, e.g. `font.size=force`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathPriority {
+    selector: Selector,
+    priority: Priority,
+}
+
+impl FromStr for PathPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (selector, priority) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("expected SELECTOR=PRIORITY, got `{}`", s))?;
+        Ok(PathPriority {
+            selector: selector.parse()?,
+            priority: priority.parse()?,
+        })
+    }
+}
+
+impl PathPriority {
+    pub fn new(selector: Selector, priority: Priority) -> PathPriority {
+        PathPriority { selector, priority }
+    }
+}
+
+/// Wraps generated values in priorities, either every value that is not an
+/// attribute set or the top level values as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Priorities {
+    priority: Option<Priority>,
+    paths: Vec<PathPriority>,
+    whole_sets: bool,
+}
+
+impl Default for Priorities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Priorities {
+    pub fn new() -> Priorities {
+        Priorities {
+            priority: None,
+            paths: vec![],
+            whole_sets: false,
+        }
+    }
+
+    /// The priority of every value without a priority of its own.
+    pub fn with_priority(mut self, priority: Priority) -> Priorities {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// The priority of the values matched by a selector. Later paths take
+    /// precedence over earlier ones.
+    pub fn add_path(mut self, path: PathPriority) -> Priorities {
+        self.paths.push(path);
+        self
+    }
+
+    /// Wraps top level values and matched attribute sets as a whole instead
+    /// of the values they contain.
+    pub fn with_whole_sets(mut self) -> Priorities {
+        self.whole_sets = true;
+        self
+    }
+
+    fn is_prioritized(value: &NixVariableValue) -> bool {
+        matches!(value, NixVariableValue::Function(name, _) if PRIORITY_FUNCTIONS.contains(&name.as_str()))
+    }
+
+    fn wrap(&self, value: &mut NixVariableValue, priority: &Priority) {
+        match value {
+            value if Priorities::is_prioritized(value) => (),
+            NixVariableValue::Comment(_) => (),
+            NixVariableValue::AttributeSet(set) if !self.whole_sets => set
+                .values_mut()
+                .for_each(|value| self.wrap(value, priority)),
+            value => *value = priority.wrap(value.to_owned()),
+        }
+    }
+
+    /// Wraps the values matched by the selectors in their priorities.
+    ///
+    /// Selectors are matched against the parsed configuration, then against
+    /// the option paths of a module, e.g. `programs.alacritty.settings.font`,
+    /// and last below its `settings`, so `font.size` also selects
+    /// `programs.alacritty.settings.font.size`. Returns an error naming the
+    /// first selector that matches nothing.
+    pub fn apply_paths(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        let settings = Selector::parse("**.settings").unwrap();
+        let mut values = values.to_vec();
+        for path in self.paths.iter().rev() {
+            let mut wrap = |value: &mut NixVariableValue| self.wrap(value, &path.priority);
+            values = path
+                .selector
+                .visit_mut(&values, &mut wrap)
+                .or_else(|_| path.selector.visit_options_mut(&values, &mut wrap))
+                .or_else(|_| {
+                    path.selector
+                        .below(&settings)
+                        .visit_options_mut(&values, &mut wrap)
+                })?;
+        }
+        Ok(values)
+    }
+
+    /// Wraps the values without a priority of their own in the priority
+    /// given with [`Priorities::with_priority`].
+    pub fn apply_default(&self, values: &[NixVariable]) -> Vec<NixVariable> {
+        let mut values = values.to_vec();
+        if let Some(priority) = &self.priority {
+            for variable in values.iter_mut() {
+                self.wrap(&mut variable.value, priority);
+            }
+        }
+        values
+    }
+
+    /// Returns the values wrapped in their priorities, or an error naming the
+    /// first selector that matches nothing.
+    pub fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(self.apply_default(&self.apply_paths(values)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapter::AdapterRegistry;
+    use crate::parser::{
+        json::JsonParser,
+        priority::{Priorities, Priority},
+        ExpressionGenerator, Parser,
+    };
+
+    #[test]
+    fn test_priorities() {
+        assert_eq!("override:50".parse(), Ok(Priority::Override(50)));
+        assert!("override".parse::<Priority>().is_err());
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let priorities = Priorities::new()
+            .with_priority(Priority::Default)
+            .add_path("font=override:50".parse().unwrap())
+            .add_path("font.size=force".parse().unwrap());
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_priorities(priorities)
+                .generate_nix_module(&parsed),
+            Some(EXPECTED.to_string())
+        );
+        assert_eq!(
+            ExpressionGenerator::new()
+                .with_priorities(
+                    Priorities::new()
+                        .with_priority(Priority::Force)
+                        .with_whole_sets()
+                )
+                .generate_nix_module(&parsed),
+            Some(EXPECTED_WHOLE_SETS.to_string())
+        );
+        assert_eq!(
+            Priorities::new()
+                .add_path("font.weight=force".parse().unwrap())
+                .apply(&parsed),
+            Err("selector `font.weight` matches nothing".to_string())
+        );
+
+        // Adapters move the configuration below programs.<NAME>.settings
+        let adapted = |selector: &str| {
+            ExpressionGenerator::new()
+                .with_adapters(AdapterRegistry::builtin())
+                .with_priorities(Priorities::new().add_path(selector.parse().unwrap()))
+                .generate_nix_expression("alacritty", &parsed)
+        };
+        assert_eq!(
+            adapted("font.size=force"),
+            Some(EXPECTED_ADAPTED.to_string())
+        );
+        assert_eq!(
+            adapted("programs.alacritty.settings.font.size=force"),
+            Some(EXPECTED_ADAPTED.to_string())
+        );
+        assert_eq!(
+            adapted("\"programs.alacritty.settings\".font.size=force"),
+            Some(EXPECTED_ADAPTED.to_string())
+        );
+        assert_eq!(adapted("size=force"), None);
+    }
+
+    const CONFIG: &str = "{
+    \"font\": { \"family\": \"Fira Code\", \"size\": 11 },
+    \"keys\": [\"a\", \"b\"],
+    \"window\": { \"padding\": { \"x\": 4 } }
+}";
+
+    const EXPECTED: &str = "{ config, lib, pkgs, ... }:
+{
+font = {
+family = lib.mkOverride 50 \"Fira Code\";
+size = lib.mkForce 11;
+};
+keys = lib.mkDefault [
+\"a\"
+\"b\"
+];
+window = {
+padding = {
+x = lib.mkDefault 4;
+};
+};
+}";
+
+    const EXPECTED_WHOLE_SETS: &str = "{ config, lib, pkgs, ... }:
+{
+font = lib.mkForce {
+family = \"Fira Code\";
+size = 11;
+};
+keys = lib.mkForce [
+\"a\"
+\"b\"
+];
+window = lib.mkForce {
+padding = {
+x = 4;
+};
+};
+}";
+
+    const EXPECTED_ADAPTED: &str = "{ config, lib, pkgs, ... }:
+{
+programs.alacritty.enable = true;
+programs.alacritty.settings = {
+font = {
+family = \"Fira Code\";
+size = lib.mkForce 11;
+};
+keys = [
+\"a\"
+\"b\"
+];
+window = {
+padding = {
+x = 4;
+};
+};
+};
+}";
+}
@@ -0,0 +1,296 @@
+use super::{NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Words opening a block in bash, zsh or fish.
+const BLOCK_START: [&str; 8] = [
+    "if", "for", "while", "until", "case", "function", "switch", "begin",
+];
+
+/// Words closing a block in bash, zsh or fish.
+const BLOCK_END: [&str; 5] = ["fi", "done", "esac", "end", "}"];
+
+/// Conservative parser for shell rc files like `.bashrc`, `.zshrc` or
+/// `config.fish`.
+///
+/// Only top level statements of a simple and unambiguous form are extracted:
+/// aliases into `aliases`, exported variables into `variables`, fish
+/// abbreviations into `abbreviations`, bash `shopt -s` options into `shopt`
+/// and zsh `setopt` options into `setopt`. Everything else is kept verbatim in
+/// `init`.
+pub struct ShellParser {}
+
+impl Default for ShellParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single classified line of a shell rc file.
+enum Statement {
+    Alias(String, String),
+    Abbreviation(String, String),
+    Export(String, String),
+    Shopt(Vec<String>),
+    Setopt(Vec<String>),
+}
+
+impl ShellParser {
+    pub fn new() -> ShellParser {
+        ShellParser {}
+    }
+
+    /// Returns the literal value of a single shell word, if it is free of
+    /// anything the shell would expand. `$VAR` references are allowed in
+    /// double quotes if `variables` is set.
+    fn literal(word: &str, variables: bool) -> Option<String> {
+        if let Some(quoted) = word.strip_prefix('\'').and_then(|w| w.strip_suffix('\'')) {
+            return (!quoted.contains('\'')).then(|| quoted.to_string());
+        }
+        let (unquoted, special) = match word.strip_prefix('"').and_then(|w| w.strip_suffix('"')) {
+            Some(quoted) => (quoted, "\"\\`"),
+            None => (word, "\"'\\`;&|<>(){}*?[]~# \t"),
+        };
+        if unquoted.is_empty()
+            || unquoted.contains(|c| special.contains(c))
+            || unquoted.contains("$(")
+            || (!variables && unquoted.contains('$'))
+        {
+            return None;
+        }
+        Some(unquoted.to_string())
+    }
+
+    /// Returns the literal value of the rest of a line, which is either a
+    /// single word or several unquoted words.
+    fn literal_words(words: &str) -> Option<String> {
+        ShellParser::literal(words, false).or_else(|| {
+            words
+                .split_whitespace()
+                .map(|word| ShellParser::literal(word, false))
+                .collect::<Option<Vec<String>>>()
+                .map(|words| words.join(" "))
+        })
+    }
+
+    /// Splits `name=value` or `name value`, the separator is required.
+    fn assignment(rest: &str, separator: char) -> Option<(String, &str)> {
+        let (name, value) = rest.split_once(separator)?;
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c));
+        valid.then(|| (name.to_string(), value.trim()))
+    }
+
+    fn parse_statement(line: &str) -> Option<Statement> {
+        let (command, rest) = line.split_once(char::is_whitespace)?;
+        let rest = rest.trim();
+        match command {
+            "alias" => {
+                // bash and zsh use `alias name=value`, fish also `alias name value`
+                let (name, value) = ShellParser::assignment(rest, '=')
+                    .filter(|(name, _)| !name.contains(char::is_whitespace))
+                    .or_else(|| ShellParser::assignment(rest, ' '))?;
+                Some(Statement::Alias(name, ShellParser::literal_words(value)?))
+            }
+            "abbr" => {
+                let rest = ["-a ", "--add "]
+                    .iter()
+                    .find_map(|flag| rest.strip_prefix(flag))
+                    .unwrap_or(rest)
+                    .trim_start();
+                if rest.starts_with('-') {
+                    return None;
+                }
+                let (name, value) = ShellParser::assignment(rest, ' ')?;
+                Some(Statement::Abbreviation(
+                    name,
+                    ShellParser::literal_words(value)?,
+                ))
+            }
+            "export" => {
+                let (name, value) = ShellParser::assignment(rest, '=')?;
+                Some(Statement::Export(name, ShellParser::literal(value, true)?))
+            }
+            "set" => {
+                let rest = ["-gx ", "-xg ", "-x ", "--export "]
+                    .iter()
+                    .find_map(|flag| rest.strip_prefix(flag))?
+                    .trim_start();
+                let (name, value) = ShellParser::assignment(rest, ' ')?;
+                Some(Statement::Export(name, ShellParser::literal(value, true)?))
+            }
+            "shopt" => Some(Statement::Shopt(ShellParser::options(
+                rest.strip_prefix("-s ")?,
+            )?)),
+            "setopt" => Some(Statement::Setopt(ShellParser::options(rest)?)),
+            "unsetopt" => Some(Statement::Setopt(
+                ShellParser::options(rest)?
+                    .into_iter()
+                    .map(|option| format!("NO_{}", option))
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn options(rest: &str) -> Option<Vec<String>> {
+        rest.split_whitespace()
+            .map(|option| {
+                option
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    .then(|| option.to_string())
+            })
+            .collect()
+    }
+
+    /// How much a line changes the nesting of blocks.
+    fn depth_change(line: &str) -> i32 {
+        let mut change = 0;
+        for statement in line.split([';', '&', '|']) {
+            match statement.split_whitespace().next() {
+                Some(word) if BLOCK_START.contains(&word) => change += 1,
+                Some(word) if BLOCK_END.contains(&word) => change -= 1,
+                _ => {}
+            }
+        }
+        if line.ends_with('{') && !line.starts_with("function") {
+            change += 1;
+        }
+        change
+    }
+}
+
+impl Parser for ShellParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut aliases = IndexMap::new();
+        let mut abbreviations = IndexMap::new();
+        let mut variables = IndexMap::new();
+        let mut shopt = vec![];
+        let mut setopt = vec![];
+        let mut init: Vec<&str> = vec![];
+        let mut depth = 0;
+        let mut continued = false;
+        for line in content.lines() {
+            let trimmed = line.trim();
+            let statement = match depth == 0 && !continued {
+                true => ShellParser::parse_statement(trimmed),
+                false => None,
+            };
+            match statement {
+                Some(Statement::Alias(name, value)) => {
+                    aliases.insert(name, NixVariableValue::String(value));
+                }
+                Some(Statement::Abbreviation(name, value)) => {
+                    abbreviations.insert(name, NixVariableValue::String(value));
+                }
+                Some(Statement::Export(name, value)) => {
+                    variables.insert(name, NixVariableValue::String(value));
+                }
+                Some(Statement::Shopt(options)) => shopt.extend(options),
+                Some(Statement::Setopt(options)) => setopt.extend(options),
+                None => {
+                    if !trimmed.starts_with('#') {
+                        depth = (depth + ShellParser::depth_change(trimmed)).max(0);
+                    }
+                    // Keep at most one empty line between unclassified lines
+                    if !trimmed.is_empty() || init.last().is_some_and(|l| !l.trim().is_empty()) {
+                        init.push(line);
+                    }
+                }
+            }
+            continued = trimmed.ends_with('\\');
+        }
+        let strings = |options: Vec<String>| {
+            NixVariableValue::List(options.into_iter().map(NixVariableValue::String).collect())
+        };
+        Some(vec![
+            NixVariable::new("aliases", &NixVariableValue::AttributeSet(aliases)),
+            NixVariable::new(
+                "abbreviations",
+                &NixVariableValue::AttributeSet(abbreviations),
+            ),
+            NixVariable::new("variables", &NixVariableValue::AttributeSet(variables)),
+            NixVariable::new("shopt", &strings(shopt)),
+            NixVariable::new("setopt", &strings(setopt)),
+            NixVariable::new(
+                "init",
+                &NixVariableValue::IndentedString(init.join("\n").trim().to_string()),
+            ),
+        ])
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{shell::ShellParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    fn strings(pairs: &[(&str, &str)]) -> NixVariableValue {
+        NixVariableValue::AttributeSet(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), NixVariableValue::String(v.to_string())))
+                .collect::<IndexMap<String, NixVariableValue>>(),
+        )
+    }
+
+    #[test]
+    fn test_shell() {
+        let parsed = ShellParser::new().parse(ZSHRC);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "aliases",
+                    &strings(&[("ll", "ls -la"), ("gs", "git status"), ("k", "kubectl")])
+                ),
+                NixVariable::new("abbreviations", &strings(&[("gco", "git checkout")])),
+                NixVariable::new(
+                    "variables",
+                    &strings(&[("EDITOR", "nvim"), ("GOPATH", "$HOME/go")])
+                ),
+                NixVariable::new("shopt", &NixVariableValue::List(vec![])),
+                NixVariable::new(
+                    "setopt",
+                    &NixVariableValue::List(vec![
+                        NixVariableValue::String("autocd".to_string()),
+                        NixVariableValue::String("extendedglob".to_string()),
+                        NixVariableValue::String("NO_beep".to_string()),
+                    ])
+                ),
+                NixVariable::new(
+                    "init",
+                    &NixVariableValue::IndentedString(
+                        "# Prompt\nif [ -n \"$SSH_CONNECTION\" ]; then\n  alias ls='ls --color'\nfi\n\nexport PATH=\"$(go env GOPATH)/bin:$PATH\"\nalias x='a' y='b'"
+                            .to_string()
+                    )
+                ),
+            ]
+        );
+    }
+
+    const ZSHRC: &str = "
+# Prompt
+alias ll='ls -la'
+alias gs=\"git status\"
+alias k=kubectl
+abbr -a gco git checkout
+if [ -n \"$SSH_CONNECTION\" ]; then
+  alias ls='ls --color'
+fi
+
+export EDITOR=nvim
+export GOPATH=\"$HOME/go\"
+export PATH=\"$(go env GOPATH)/bin:$PATH\"
+setopt autocd extendedglob
+unsetopt beep
+alias x='a' y='b'
+";
+}
@@ -0,0 +1,210 @@
+use super::{NixVariable, NixVariableValue};
+use std::path::PathBuf;
+
+/// The file importing all others.
+const ENTRY_MODULE: &str = "default.nix";
+
+/// Splits a generated module into several files, so changes to a section
+/// only touch its own file.
+///
+/// Attribute sets of options are split into their keys, e.g.
+/// `programs.alacritty.settings` into one section for `font` and one for
+/// `window`, which become modules defining `programs.alacritty.settings.font`
+/// and `programs.alacritty.settings.window`. Other attribute sets are a
+/// section of their own. The entry module `default.nix` imports the sections
+/// and keeps all other values.
+pub struct Split {
+    /// The number of lines sections are grouped into files by, `None` for a
+    /// file per section.
+    max_lines: Option<usize>,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Split {
+    pub fn new() -> Split {
+        Split { max_lines: None }
+    }
+
+    /// Groups consecutive sections into files of up to `max_lines` lines
+    /// instead of writing a file per section. Larger sections get a file of
+    /// their own.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Split {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    /// A file name for a key, keeping only characters that need no quoting
+    /// in nix paths.
+    fn file_name(key: &str, files: &[(String, Vec<NixVariable>)]) -> String {
+        let name = key
+            .chars()
+            .map(|c| match c {
+                c if c.is_ascii_alphanumeric() || "_-".contains(c) => c,
+                _ => '-',
+            })
+            .collect::<String>()
+            .trim_matches('-')
+            .to_string();
+        let name = match name.as_str() {
+            "" => "section".to_string(),
+            "default" => "default-section".to_string(),
+            _ => name,
+        };
+        let mut unique = format!("{}.nix", name);
+        let mut index = 1;
+        while files.iter().any(|(file, _)| *file == unique) {
+            index += 1;
+            unique = format!("{}-{}.nix", name, index);
+        }
+        unique
+    }
+
+    /// Returns the files with the variables defined in them, the entry module
+    /// first.
+    pub fn split(&self, values: &[NixVariable]) -> Vec<(String, Vec<NixVariable>)> {
+        let mut entry = vec![];
+        let mut sections: Vec<(String, NixVariable)> = vec![];
+        for variable in values {
+            let is_option = variable.name.contains('.') && !variable.name.starts_with('"');
+            match &variable.value {
+                NixVariableValue::AttributeSet(set) if is_option && !set.is_empty() => sections
+                    .extend(set.iter().map(|(key, value)| {
+                        (
+                            key.to_owned(),
+                            NixVariable::new(
+                                &format!("{}.{}", variable.name, super::attr_name(key)),
+                                value,
+                            ),
+                        )
+                    })),
+                NixVariableValue::AttributeSet(_) => {
+                    sections.push((variable.name.to_owned(), variable.to_owned()))
+                }
+                _ => entry.push(variable.to_owned()),
+            }
+        }
+        let mut files: Vec<(String, Vec<NixVariable>)> = vec![];
+        match self.max_lines {
+            None => {
+                for (key, section) in sections {
+                    let file = Split::file_name(&key, &files);
+                    files.push((file, vec![section]));
+                }
+            }
+            Some(max_lines) => {
+                let mut lines = 0;
+                for (_, section) in sections {
+                    let length = section.to_string().lines().count();
+                    match files.last_mut() {
+                        Some((_, part)) if lines + length <= max_lines => part.push(section),
+                        _ => {
+                            lines = 0;
+                            files.push((format!("part-{}.nix", files.len() + 1), vec![section]));
+                        }
+                    }
+                    lines += length;
+                }
+            }
+        }
+        if !files.is_empty() {
+            entry.push(NixVariable::new(
+                "imports",
+                &NixVariableValue::List(
+                    files
+                        .iter()
+                        .map(|(file, _)| {
+                            NixVariableValue::Path(Box::new(PathBuf::from(format!("./{}", file))))
+                        })
+                        .collect(),
+                ),
+            ));
+        }
+        std::iter::once((ENTRY_MODULE.to_string(), entry))
+            .chain(files)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        json::JsonParser, priority::Priorities, split::Split, ExpressionGenerator, Parser,
+    };
+
+    #[test]
+    fn test_split() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let generate = |split: &Split| {
+            ExpressionGenerator::new()
+                .split_nix_expression("mytool", &parsed, split)
+                .unwrap()
+                .into_iter()
+                .map(|(file, expression)| format!("# {}\n{}", file, expression))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        assert_eq!(generate(&Split::new()), EXPECTED);
+        assert_eq!(
+            generate(&Split::new().with_max_lines(6))
+                .lines()
+                .filter(|line| line.starts_with("# "))
+                .collect::<Vec<&str>>(),
+            vec!["# default.nix", "# part-1.nix", "# part-2.nix"]
+        );
+
+        // Selectors are matched in the whole module, not in every file
+        let files = ExpressionGenerator::new()
+            .with_priorities(Priorities::new().add_path("font.size=force".parse().unwrap()))
+            .split_nix_expression("mytool", &parsed, &Split::new())
+            .unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|(file, _)| file.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["default.nix", "font.nix", "window.nix"]
+        );
+        assert!(files[1].1.contains("size = lib.mkForce 11;"));
+        assert!(!files[0].1.contains("lib."));
+    }
+
+    const CONFIG: &str = "{
+    \"font\": { \"family\": \"Fira Code\", \"size\": 11 },
+    \"theme\": \"nord\",
+    \"window\": { \"padding\": { \"x\": 4 }, \"title\": \"default\" }
+}";
+
+    const EXPECTED: &str = "# default.nix
+{ config, pkgs, ... }:
+{
+programs.mytool.enable = true;
+theme = \"nord\";
+imports = [
+./font.nix
+./window.nix
+];
+}
+# font.nix
+{ config, pkgs, ... }:
+{
+font = {
+family = \"Fira Code\";
+size = 11;
+};
+}
+# window.nix
+{ config, pkgs, ... }:
+{
+window = {
+padding = {
+x = 4;
+};
+title = \"default\";
+};
+}";
+}
@@ -0,0 +1,91 @@
+use super::{keyword::KeywordValueParser, Parser};
+
+/// Parser for OpenSSH client configuration files (`~/.ssh/config`).
+///
+/// Options in front of the first block are returned as plain variables, every
+/// `Host` or `Match` block becomes an attribute set named after its header,
+/// e.g. `Host github.com`. Repeated keywords are collected into lists.
+pub struct SshConfigParser {
+    parser: KeywordValueParser,
+}
+
+impl Default for SshConfigParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SshConfigParser {
+    pub fn new() -> SshConfigParser {
+        SshConfigParser {
+            parser: KeywordValueParser::new().with_block_keywords(&["Host", "Match"]),
+        }
+    }
+}
+
+impl Parser for SshConfigParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        self.parser.parse(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{ssh::SshConfigParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+    use lazy_static::lazy_static;
+
+    #[test]
+    fn test_ssh_config() {
+        let parser = SshConfigParser::new();
+
+        let parsed = parser.parse(SSH_CONFIG);
+        assert!(parsed.is_some());
+        assert_eq!(parsed.unwrap(), *EXPECTED);
+        assert!(parser.parse("foo:\n  bar: 1").is_none());
+    }
+
+    lazy_static! {
+        pub static ref EXPECTED: Vec<NixVariable> = vec![
+            NixVariable::new(
+                "AddKeysToAgent",
+                &NixVariableValue::String("yes".to_string())
+            ),
+            NixVariable::new(
+                "Host github.com",
+                &NixVariableValue::AttributeSet(IndexMap::from([
+                    (
+                        "User".to_string(),
+                        NixVariableValue::String("git".to_string())
+                    ),
+                    (
+                        "IdentityFile".to_string(),
+                        NixVariableValue::List(vec![
+                            NixVariableValue::String("~/.ssh/id_ed25519".to_string()),
+                            NixVariableValue::String("~/.ssh/my key".to_string()),
+                        ]),
+                    ),
+                ])),
+            ),
+            NixVariable::new(
+                "Match host *.internal exec \"test -f /tmp/vpn\"",
+                &NixVariableValue::AttributeSet(IndexMap::from([(
+                    "Port".to_string(),
+                    NixVariableValue::String("2222".to_string()),
+                )])),
+            ),
+        ];
+    }
+    const SSH_CONFIG: &str = "
+# Global options
+AddKeysToAgent yes
+
+Host github.com
+    User git
+    IdentityFile ~/.ssh/id_ed25519
+    IdentityFile=\"~/.ssh/my key\"
+
+Match host *.internal exec \"test -f /tmp/vpn\"
+    Port 2222
+";
+}
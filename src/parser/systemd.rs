@@ -0,0 +1,131 @@
+use super::{insert_or_append, NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for systemd unit files like `.service`, `.timer` or `.socket` units.
+///
+/// Every section becomes an attribute set of strings. Keys that are assigned
+/// multiple times are collected into lists, so an empty assignment resetting a
+/// list is kept as an empty string. Values are kept verbatim, including
+/// `-`/`@`/`+` prefixes of `Exec*` lines and specifiers like `%h`.
+///
+/// XDG key files like `mimeapps.list` share the syntax and are parsed the same
+/// way.
+pub struct SystemdUnitParser {}
+
+impl Default for SystemdUnitParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemdUnitParser {
+    pub fn new() -> SystemdUnitParser {
+        SystemdUnitParser {}
+    }
+}
+
+impl Parser for SystemdUnitParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut sections: IndexMap<String, IndexMap<String, NixVariableValue>> = IndexMap::new();
+        let mut section = None;
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let mut line = line.trim().to_string();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            // Join continuation lines, comments in between are skipped
+            while line.ends_with('\\') {
+                line.pop();
+                line.truncate(line.trim_end().len());
+                match lines.next().map(str::trim) {
+                    Some(next) if next.starts_with('#') || next.starts_with(';') => {}
+                    Some(next) => line.push_str(&format!(" {}", next)),
+                    None => break,
+                }
+            }
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name.strip_suffix(']')?.trim().to_string();
+                sections.entry(name.to_owned()).or_default();
+                section = Some(name);
+                continue;
+            }
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+            insert_or_append(
+                sections.get_mut(section.as_ref()?)?,
+                key.to_string(),
+                NixVariableValue::String(value.trim().to_string()),
+            );
+        }
+        Some(
+            sections
+                .into_iter()
+                .map(|(name, section)| NixVariable {
+                    name,
+                    value: NixVariableValue::AttributeSet(section),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{systemd::SystemdUnitParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+    use lazy_static::lazy_static;
+
+    #[test]
+    fn test_systemd_unit() {
+        let parser = SystemdUnitParser::new();
+
+        let parsed = parser.parse(UNIT);
+        assert!(parsed.is_some());
+        assert_eq!(parsed.unwrap(), *EXPECTED);
+        assert!(parser.parse("Description=no section").is_none());
+    }
+
+    lazy_static! {
+        pub static ref EXPECTED: Vec<NixVariable> = vec![
+            NixVariable::new(
+                "Unit",
+                &NixVariableValue::AttributeSet(IndexMap::from([(
+                    "Description".to_string(),
+                    NixVariableValue::String("Backup job".to_string()),
+                )])),
+            ),
+            NixVariable::new(
+                "Service",
+                &NixVariableValue::AttributeSet(IndexMap::from([
+                    (
+                        "ExecStartPre".to_string(),
+                        NixVariableValue::String("-/usr/bin/mkdir -p %h/backup".to_string()),
+                    ),
+                    (
+                        "ExecStart".to_string(),
+                        NixVariableValue::List(vec![
+                            NixVariableValue::String("".to_string()),
+                            NixVariableValue::String(
+                                "/usr/bin/rsync -a --delete /data %h/backup".to_string()
+                            ),
+                        ]),
+                    ),
+                ])),
+            ),
+        ];
+    }
+    const UNIT: &str = "
+[Unit]
+Description=Backup job
+
+[Service]
+ExecStartPre=-/usr/bin/mkdir -p %h/backup
+ExecStart=
+ExecStart=/usr/bin/rsync -a \\
+    --delete /data %h/backup
+";
+}
@@ -0,0 +1,361 @@
+use super::{
+    attr_name,
+    nix::{module_set, NixParser},
+    NixVariableValue, Parser,
+};
+use crate::transform::{
+    diff::{diff, Difference},
+    to_value,
+};
+use rnix::{
+    types::{AttrSet, EntryHolder, KeyValue, Lambda, Pattern, TokenWrapper, TypedNode, Wrapper},
+    SyntaxKind,
+};
+
+/// A replacement of a range of the file.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Returns the key path of an entry, if it has no interpolated keys.
+fn keys(entry: &KeyValue) -> Option<Vec<String>> {
+    entry.key()?.path().map(NixParser::key).collect()
+}
+
+/// Finds the entry defining the value at a path. Otherwise returns the
+/// deepest attribute set on the path and the number of keys leading to it.
+fn find(set: AttrSet, path: &[String]) -> Result<KeyValue, (AttrSet, usize)> {
+    let mut deepest = (set.clone(), 0);
+    for entry in set.entries() {
+        let Some(keys) = keys(&entry) else {
+            continue;
+        };
+        if !path.starts_with(&keys) {
+            continue;
+        }
+        if keys.len() == path.len() {
+            return Ok(entry);
+        }
+        let Some(nested) = entry
+            .value()
+            .and_then(AttrSet::cast)
+            .filter(|nested| !nested.recursive())
+        else {
+            continue;
+        };
+        match find(nested, &path[keys.len()..]) {
+            Ok(entry) => return Ok(entry),
+            Err((nested, depth)) if keys.len() + depth > deepest.1 => {
+                deepest = (nested, keys.len() + depth)
+            }
+            Err(_) => (),
+        }
+    }
+    Err(deepest)
+}
+
+/// Returns the value at a path of a tree.
+fn lookup<'a>(value: &'a NixVariableValue, path: &[String]) -> Option<&'a NixVariableValue> {
+    match path.split_first() {
+        None => Some(value),
+        Some((key, rest)) => match value {
+            NixVariableValue::AttributeSet(set) => lookup(set.get(key)?, rest),
+            _ => None,
+        },
+    }
+}
+
+/// The indentation of the line containing an offset.
+fn indentation(content: &str, offset: usize) -> &str {
+    let start = content[..offset]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let line = &content[start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Formats a value to be written at the given indentation.
+fn render(value: &NixVariableValue, indentation: &str) -> String {
+    nixpkgs_fmt::reformat_string(&value.to_string())
+        .trim()
+        .replace('\n', &format!("\n{}", indentation))
+}
+
+/// Removes an entry together with its line, if it is the only entry on it.
+fn removal(content: &str, entry: &KeyValue) -> Edit {
+    let range = entry.node().text_range();
+    let (mut start, mut end) = (usize::from(range.start()), usize::from(range.end()));
+    let before = content[..start].trim_end_matches([' ', '\t']);
+    let after = content[end..].trim_start_matches([' ', '\t']);
+    if (before.is_empty() || before.ends_with('\n')) && after.starts_with('\n') {
+        start = before.len();
+        end = content.len() - after.len() + 1;
+    }
+    Edit {
+        start,
+        end,
+        text: String::new(),
+    }
+}
+
+/// Inserts an entry before the closing brace of an attribute set, indented
+/// like the other entries.
+fn insertion(
+    content: &str,
+    set: &AttrSet,
+    keys: &[String],
+    value: &NixVariableValue,
+) -> Option<Edit> {
+    let key = keys
+        .iter()
+        .map(|key| attr_name(key))
+        .collect::<Vec<String>>()
+        .join(".");
+    let brace = usize::from(set.node().last_token()?.text_range().start());
+    let line_start = content[..brace].rfind('\n').map(|newline| newline + 1)?;
+    if !content[line_start..brace].trim().is_empty() {
+        // The set is written on a single line, e.g. `{ a = 1; }`
+        return Some(Edit {
+            start: brace,
+            end: brace,
+            text: format!("{} = {}; ", key, render(value, "")),
+        });
+    }
+    let indentation = match set.entries().next() {
+        Some(entry) => {
+            indentation(content, usize::from(entry.node().text_range().start())).to_string()
+        }
+        None => format!("{}  ", indentation(content, brace)),
+    };
+    Some(Edit {
+        start: line_start,
+        end: line_start,
+        text: format!(
+            "{}{} = {};\n",
+            indentation,
+            key,
+            render(value, &indentation)
+        ),
+    })
+}
+
+/// Returns the argument set of a module, e.g. `{ config, pkgs, ... }:`.
+fn arguments(content: &str) -> Option<Pattern> {
+    Lambda::cast(rnix::parse(content).root().inner()?)?
+        .arg()
+        .and_then(Pattern::cast)
+}
+
+/// Whether an argument set takes an argument.
+fn takes(pattern: &Pattern, name: &str) -> bool {
+    pattern
+        .entries()
+        .any(|entry| entry.name().is_some_and(|ident| ident.as_str() == name))
+}
+
+/// Adds `lib` to the argument set of a module, after `config` if it takes it.
+fn lib_argument(pattern: &Pattern) -> Option<Edit> {
+    let at = |offset: rnix::TextSize, text: &str| Edit {
+        start: usize::from(offset),
+        end: usize::from(offset),
+        text: text.to_string(),
+    };
+    let token = |kind| {
+        pattern
+            .node()
+            .children_with_tokens()
+            .find(|child| child.kind() == kind)
+            .map(|child| child.text_range().start())
+    };
+    if let Some(entry) = pattern
+        .entries()
+        .find(|entry| entry.name().is_none_or(|ident| ident.as_str() != "config"))
+    {
+        return Some(at(entry.node().text_range().start(), "lib, "));
+    }
+    if let Some(ellipsis) = token(SyntaxKind::TOKEN_ELLIPSIS) {
+        return Some(at(ellipsis, "lib, "));
+    }
+    if let Some(last) = pattern.entries().last() {
+        return Some(at(last.node().text_range().end(), ", lib"));
+    }
+    Some(at(token(SyntaxKind::TOKEN_CURLY_B_CLOSE)?, "lib "))
+}
+
+/// Compares a nix file with a generated module by the attribute paths of
+/// their values. Values missing in the generated module are only reported
+/// below the options it defines, e.g. below `programs.alacritty.settings`,
+/// so manual additions elsewhere are no differences. Returns `None` if either
+/// file is no attribute set.
+pub fn differences(content: &str, generated: &str) -> Option<Vec<Difference>> {
+    let parser = NixParser::new();
+    let options = module_set(generated)?
+        .entries()
+        .map(|entry| keys(&entry))
+        .collect::<Option<Vec<Vec<String>>>>()?;
+    Some(
+        diff(&parser.parse(content)?, &parser.parse(generated)?)
+            .into_iter()
+            .filter(|difference| match difference {
+                Difference::Removed(path, _) => {
+                    options.iter().any(|option| path.starts_with(option))
+                }
+                _ => true,
+            })
+            .collect(),
+    )
+}
+
+/// Updates a nix file in place to define the values of a generated module,
+/// keeping its formatting, comments and manual additions.
+///
+/// Changed values are replaced where they are defined, new ones are added to
+/// the deepest attribute set on their path and removed ones are deleted, see
+/// [`differences`]. `lib` is added to the arguments of the file if the
+/// generated module takes it. Returns `None` if either file is no attribute set or a
+/// value can't be located.
+pub fn update(content: &str, generated: &str) -> Option<String> {
+    let set = module_set(content)?;
+    let new_tree = to_value(&NixParser::new().parse(generated)?);
+
+    let mut edits = vec![];
+    if arguments(generated).is_some_and(|pattern| takes(&pattern, "lib")) {
+        let pattern = arguments(content)?;
+        if !takes(&pattern, "lib") {
+            edits.push(lib_argument(&pattern)?);
+        }
+    }
+    let mut added: Vec<Vec<String>> = vec![];
+    for difference in differences(content, generated)? {
+        match difference {
+            Difference::Changed(path, _, value) => {
+                let node = find(set.clone(), &path).ok()?.value()?;
+                let start = usize::from(node.text_range().start());
+                edits.push(Edit {
+                    start,
+                    end: usize::from(node.text_range().end()),
+                    text: render(&value, indentation(content, start)),
+                });
+            }
+            Difference::Removed(path, _) => {
+                edits.push(removal(content, &find(set.clone(), &path).ok()?))
+            }
+            Difference::Added(path, _) => {
+                if added.iter().any(|prefix| path.starts_with(prefix)) {
+                    continue;
+                }
+                let Err((parent, depth)) = find(set.clone(), &path) else {
+                    return None;
+                };
+                // Add the whole set missing in the file at once
+                let keys = &path[..depth + 1];
+                edits.push(insertion(
+                    content,
+                    &parent,
+                    &keys[depth..],
+                    lookup(&new_tree, keys)?,
+                )?);
+                added.push(keys.to_vec());
+            }
+        }
+    }
+
+    // Apply the edits from the end, so the offsets of the others stay valid.
+    // Insertions at the same offset keep their order.
+    edits.sort_by_key(|edit| edit.start);
+    let mut updated = content.to_string();
+    for edit in edits.iter().rev() {
+        updated.replace_range(edit.start..edit.end, &edit.text);
+    }
+    Some(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::update::update;
+
+    #[test]
+    fn test_update() {
+        assert_eq!(update(EXISTING, GENERATED), Some(EXPECTED.to_string()));
+        assert_eq!(update(EXPECTED, GENERATED), Some(EXPECTED.to_string()));
+        assert_eq!(update("[ ]", GENERATED), None);
+
+        let generated = GENERATED
+            .replace("{ config, pkgs, ... }:", "{ config, lib, pkgs, ... }:")
+            .replace("size = 13;", "size = lib.mkDefault 13;");
+        let expected = EXPECTED
+            .replace("{ config, pkgs, ... }:", "{ config, lib, pkgs, ... }:")
+            .replace("size = 13;", "size = lib.mkDefault 13;");
+        assert_eq!(update(EXISTING, &generated), Some(expected.clone()));
+        assert_eq!(update(&expected, &generated), Some(expected.clone()));
+        assert_eq!(
+            update("{ config }:\n{ }\n", "{ config, lib }:\n{ }\n"),
+            Some("{ config, lib }:\n{ }\n".to_string())
+        );
+        assert_eq!(
+            update("{ ... }:\n{ }\n", "{ lib, ... }:\n{ }\n"),
+            Some("{ lib, ... }:\n{ }\n".to_string())
+        );
+    }
+
+    const EXISTING: &str = "{ config, pkgs, ... }:
+{
+  # Edited by hand, keep it
+  home.packages = [ pkgs.ripgrep ];
+
+  programs.alacritty.enable = true;
+  programs.alacritty.settings = {
+    font = { family = \"Fira Code\"; size = 11; }; # bigger on the laptop
+    # no bell
+    bell.duration = 0;
+    keyboard.bindings = [
+      { key = \"N\"; action = \"SpawnNewInstance\"; }
+    ];
+  };
+}
+";
+
+    const GENERATED: &str = "{ config, pkgs, ... }:
+{
+  programs.alacritty.enable = true;
+  programs.alacritty.settings = {
+    font = {
+      family = \"Fira Code\";
+      size = 13;
+      style = \"Regular\";
+    };
+    keyboard.bindings = [
+      { key = \"N\"; action = \"SpawnNewInstance\"; }
+    ];
+    window = {
+      opacity = 0.9;
+      padding = { x = 4; };
+    };
+  };
+}
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+  # Edited by hand, keep it
+  home.packages = [ pkgs.ripgrep ];
+
+  programs.alacritty.enable = true;
+  programs.alacritty.settings = {
+    font = { family = \"Fira Code\"; size = 13; style = \"Regular\"; }; # bigger on the laptop
+    # no bell
+    keyboard.bindings = [
+      { key = \"N\"; action = \"SpawnNewInstance\"; }
+    ];
+    window = {
+      opacity = 0.9;
+      padding = {
+        x = 4;
+      };
+    };
+  };
+}
+";
+}
@@ -0,0 +1,163 @@
+use super::{NixVariable, NixVariableValue, Parser};
+use indexmap::IndexMap;
+
+/// Parser for X resource files like `.Xresources`.
+///
+/// Resources are collected into `properties`, with `#define` macros expanded
+/// in names and values. Integers and booleans are typed, everything else is
+/// kept as a string. Preprocessor directives that can't be resolved, like
+/// `#include` or `#ifdef`, are listed in `unresolved`.
+pub struct XresourcesParser {}
+
+impl Default for XresourcesParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XresourcesParser {
+    pub fn new() -> XresourcesParser {
+        XresourcesParser {}
+    }
+
+    /// Replaces all identifiers that are defined macros.
+    fn expand(text: &str, macros: &IndexMap<String, String>) -> String {
+        let mut result = String::new();
+        let mut identifier = String::new();
+        for c in text.chars().chain(std::iter::once('\0')) {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                identifier.push(c);
+                continue;
+            }
+            match macros.get(&identifier) {
+                Some(value) => result.push_str(value),
+                None => result.push_str(&identifier),
+            }
+            identifier.clear();
+            if c != '\0' {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    fn typed(value: String) -> NixVariableValue {
+        match value.as_str() {
+            "true" => NixVariableValue::Boolean(true),
+            "false" => NixVariableValue::Boolean(false),
+            number => match number.parse::<i64>() {
+                Ok(i) => NixVariableValue::Number(i as f64),
+                Err(_) => NixVariableValue::String(value),
+            },
+        }
+    }
+}
+
+impl Parser for XresourcesParser {
+    fn parse(&self, content: &str) -> Option<Vec<super::NixVariable>> {
+        let mut properties = IndexMap::new();
+        let mut macros: IndexMap<String, String> = IndexMap::new();
+        let mut unresolved = vec![];
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let mut line = line.trim().to_string();
+            if line.is_empty() || line.starts_with('!') {
+                continue;
+            }
+            while line.ends_with('\\') {
+                line.pop();
+                match lines.next() {
+                    Some(next) => line.push_str(next),
+                    None => break,
+                }
+            }
+            if let Some(directive) = line.strip_prefix('#') {
+                let directive = directive.trim_start();
+                match directive.strip_prefix("define") {
+                    Some(definition) if definition.starts_with(char::is_whitespace) => {
+                        let definition = definition.trim();
+                        let (name, value) = definition
+                            .split_once(char::is_whitespace)
+                            .unwrap_or((definition, ""));
+                        // Function-like macros can't be expanded
+                        if name.contains('(') {
+                            unresolved.push(NixVariableValue::String(line.to_owned()));
+                            continue;
+                        }
+                        let value = XresourcesParser::expand(value.trim(), &macros);
+                        macros.insert(name.to_string(), value);
+                    }
+                    _ => unresolved.push(NixVariableValue::String(line.to_owned())),
+                }
+                continue;
+            }
+            let (name, value) = line.split_once(':')?;
+            let name = XresourcesParser::expand(name.trim(), &macros);
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return None;
+            }
+            let value = XresourcesParser::expand(value.trim(), &macros);
+            properties.insert(name, XresourcesParser::typed(value));
+        }
+        Some(vec![
+            NixVariable::new("properties", &NixVariableValue::AttributeSet(properties)),
+            NixVariable::new("unresolved", &NixVariableValue::List(unresolved)),
+        ])
+    }
+
+    fn guessable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{xresources::XresourcesParser, NixVariable, NixVariableValue, Parser};
+    use indexmap::IndexMap;
+
+    fn string(s: &str) -> NixVariableValue {
+        NixVariableValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_xresources() {
+        let parser = XresourcesParser::new();
+
+        let parsed = parser.parse(XRESOURCES);
+        assert!(parsed.is_some());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                NixVariable::new(
+                    "properties",
+                    &NixVariableValue::AttributeSet(IndexMap::from([
+                        ("*.foreground".to_string(), string("#c5c8c6")),
+                        ("*.background".to_string(), string("#1d1f21")),
+                        ("URxvt.font".to_string(), string("xft:Fira Code:size=11")),
+                        ("Xft.dpi".to_string(), NixVariableValue::Number(96.0)),
+                        ("Xft.antialias".to_string(), NixVariableValue::Boolean(true)),
+                    ]))
+                ),
+                NixVariable::new(
+                    "unresolved",
+                    &NixVariableValue::List(vec![string("#include \".Xresources.d/colors\"")])
+                ),
+            ]
+        );
+        assert!(parser.parse("not a resource").is_none());
+    }
+
+    const XRESOURCES: &str = "
+! Colors
+#define FG #c5c8c6
+#define BG #1d1f21
+#include \".Xresources.d/colors\"
+
+*.foreground: FG
+*.background:   BG
+URxvt.font: xft:Fira Code:\\
+size=11
+Xft.dpi: 96
+Xft.antialias: true
+";
+}
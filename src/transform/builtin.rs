@@ -0,0 +1,221 @@
+use super::{visit_mut, Transform};
+use crate::parser::{NixVariable, NixVariableValue};
+use indexmap::IndexMap;
+use regex::Regex;
+use std::str::FromStr;
+
+/// Drops attributes and list elements that are `null`.
+pub struct DropNulls {}
+
+impl Default for DropNulls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DropNulls {
+    pub fn new() -> DropNulls {
+        DropNulls {}
+    }
+}
+
+impl Transform for DropNulls {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(visit_mut(values, &mut |value| match value {
+            NixVariableValue::AttributeSet(set) => {
+                set.retain(|_, value| *value != NixVariableValue::Null)
+            }
+            NixVariableValue::List(list) => list.retain(|value| *value != NixVariableValue::Null),
+            _ => (),
+        }))
+    }
+}
+
+/// Sorts the keys of every attribute set alphabetically.
+pub struct SortKeys {}
+
+impl Default for SortKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SortKeys {
+    pub fn new() -> SortKeys {
+        SortKeys {}
+    }
+}
+
+impl Transform for SortKeys {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(visit_mut(values, &mut |value| {
+            if let NixVariableValue::AttributeSet(set) = value {
+                set.sort_keys()
+            }
+        }))
+    }
+}
+
+/// Drops repeated elements of lists, keeping the first one.
+pub struct DedupeLists {}
+
+impl Default for DedupeLists {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DedupeLists {
+    pub fn new() -> DedupeLists {
+        DedupeLists {}
+    }
+}
+
+impl Transform for DedupeLists {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(visit_mut(values, &mut |value| {
+            if let NixVariableValue::List(list) = value {
+                let mut unique: Vec<NixVariableValue> = vec![];
+                for element in list.drain(..) {
+                    if !unique.contains(&element) {
+                        unique.push(element);
+                    }
+                }
+                *list = unique;
+            }
+        }))
+    }
+}
+
+/// Renames the keys of every attribute set. If two keys end up with the same
+/// name, the latter one is kept.
+fn rename_keys(values: &[NixVariable], rename: &dyn Fn(&str) -> String) -> Vec<NixVariable> {
+    visit_mut(values, &mut |value| {
+        if let NixVariableValue::AttributeSet(set) = value {
+            *set = set
+                .drain(..)
+                .map(|(key, value)| (rename(&key), value))
+                .collect::<IndexMap<String, NixVariableValue>>();
+        }
+    })
+}
+
+/// Converts every key to lowercase.
+pub struct LowercaseKeys {}
+
+impl Default for LowercaseKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LowercaseKeys {
+    pub fn new() -> LowercaseKeys {
+        LowercaseKeys {}
+    }
+}
+
+impl Transform for LowercaseKeys {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(rename_keys(values, &|key| key.to_lowercase()))
+    }
+}
+
+/// Replaces the matches of a regular expression in every key, the
+/// replacement can refer to capture groups like `$1`.
+pub struct RenameKeys {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RenameKeys {
+    pub fn new(pattern: &str, replacement: &str) -> Option<RenameKeys> {
+        Some(RenameKeys {
+            pattern: Regex::new(pattern).ok()?,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl Transform for RenameKeys {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(rename_keys(values, &|key| {
+            self.pattern
+                .replace_all(key, self.replacement.as_str())
+                .to_string()
+        }))
+    }
+}
+
+/// The built-in transforms as given on the command line, e.g. `sort-keys` or
+/// `rename:^Font(.*)$=font$1`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuiltinTransform {
+    DropNulls,
+    SortKeys,
+    LowercaseKeys,
+    DedupeLists,
+    Rename(String, String),
+}
+
+impl FromStr for BuiltinTransform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-nulls" => Ok(BuiltinTransform::DropNulls),
+            "sort-keys" => Ok(BuiltinTransform::SortKeys),
+            "lowercase-keys" => Ok(BuiltinTransform::LowercaseKeys),
+            "dedupe-lists" => Ok(BuiltinTransform::DedupeLists),
+            s => {
+                let (pattern, replacement) = s
+                    .strip_prefix("rename:")
+                    .and_then(|rename| rename.rsplit_once('='))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown transform `{}`, expected drop-nulls, sort-keys, lowercase-keys, dedupe-lists or rename:PATTERN=REPLACEMENT",
+                            s
+                        )
+                    })?;
+                Regex::new(pattern).map_err(|e| e.to_string())?;
+                Ok(BuiltinTransform::Rename(
+                    pattern.to_string(),
+                    replacement.to_string(),
+                ))
+            }
+        }
+    }
+}
+
+impl BuiltinTransform {
+    pub fn transform(&self) -> Box<dyn Transform> {
+        match self {
+            BuiltinTransform::DropNulls => Box::new(DropNulls::new()),
+            BuiltinTransform::SortKeys => Box::new(SortKeys::new()),
+            BuiltinTransform::LowercaseKeys => Box::new(LowercaseKeys::new()),
+            BuiltinTransform::DedupeLists => Box::new(DedupeLists::new()),
+            BuiltinTransform::Rename(pattern, replacement) => {
+                Box::new(RenameKeys::new(pattern, replacement).expect("Validated when parsing"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transform::builtin::BuiltinTransform;
+
+    #[test]
+    fn test_builtin_transform_parsing() {
+        assert_eq!("sort-keys".parse(), Ok(BuiltinTransform::SortKeys));
+        assert_eq!(
+            "rename:^(.*)_(.*)$=$1-$2".parse(),
+            Ok(BuiltinTransform::Rename(
+                "^(.*)_(.*)$".to_string(),
+                "$1-$2".to_string()
+            ))
+        );
+        assert!("rename:(=x".parse::<BuiltinTransform>().is_err());
+        assert!("reverse".parse::<BuiltinTransform>().is_err());
+    }
+}
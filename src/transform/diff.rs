@@ -0,0 +1,161 @@
+use super::to_value;
+use crate::parser::{attr_name, NixVariable, NixVariableValue};
+use std::fmt;
+
+/// A difference between two configurations at a key path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// A value only the new configuration has.
+    Added(Vec<String>, NixVariableValue),
+    /// A value only the old configuration has.
+    Removed(Vec<String>, NixVariableValue),
+    /// A value both configurations have, old and new.
+    Changed(Vec<String>, NixVariableValue, NixVariableValue),
+}
+
+/// Writes a value on a single line.
+fn inline(value: &NixVariableValue) -> String {
+    value
+        .to_string()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn path(path: &[String]) -> String {
+    path.iter()
+        .map(|key| attr_name(key))
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::Added(p, value) => write!(f, "+ {} = {}", path(p), inline(value)),
+            Difference::Removed(p, value) => write!(f, "- {} = {}", path(p), inline(value)),
+            Difference::Changed(p, old, new) => {
+                write!(f, "~ {} = {} -> {}", path(p), inline(old), inline(new))
+            }
+        }
+    }
+}
+
+/// Whether two values are the same, ignoring the formatting of expressions.
+fn same(old: &NixVariableValue, new: &NixVariableValue) -> bool {
+    match (old, new) {
+        (NixVariableValue::Expression(old), NixVariableValue::Expression(new)) => {
+            old.split_whitespace().eq(new.split_whitespace())
+        }
+        (NixVariableValue::List(old), NixVariableValue::List(new)) => {
+            old.len() == new.len() && old.iter().zip(new).all(|(old, new)| same(old, new))
+        }
+        (old, new) => old == new,
+    }
+}
+
+/// Reports every value of a set on its own.
+fn leaves(
+    value: &NixVariableValue,
+    path: &mut Vec<String>,
+    difference: &dyn Fn(Vec<String>, NixVariableValue) -> Difference,
+    differences: &mut Vec<Difference>,
+) {
+    match value {
+        NixVariableValue::AttributeSet(set) if !set.is_empty() => {
+            for (key, value) in set {
+                path.push(key.to_owned());
+                leaves(value, path, difference, differences);
+                path.pop();
+            }
+        }
+        value => differences.push(difference(path.to_owned(), value.to_owned())),
+    }
+}
+
+fn compare(
+    old: &NixVariableValue,
+    new: &NixVariableValue,
+    path: &mut Vec<String>,
+    differences: &mut Vec<Difference>,
+) {
+    match (old, new) {
+        (NixVariableValue::AttributeSet(old), NixVariableValue::AttributeSet(new)) => {
+            for (key, old) in old {
+                path.push(key.to_owned());
+                match new.get(key) {
+                    Some(new) => compare(old, new, path, differences),
+                    None => leaves(old, path, &Difference::Removed, differences),
+                }
+                path.pop();
+            }
+            for (key, new) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                path.push(key.to_owned());
+                leaves(new, path, &Difference::Added, differences);
+                path.pop();
+            }
+        }
+        (old, new) if !same(old, new) => differences.push(Difference::Changed(
+            path.to_owned(),
+            old.to_owned(),
+            new.to_owned(),
+        )),
+        _ => (),
+    }
+}
+
+/// Compares two configurations by the key paths of their values. Attribute
+/// sets are compared key by key, other values as a whole.
+pub fn diff(old: &[NixVariable], new: &[NixVariable]) -> Vec<Difference> {
+    let mut differences = vec![];
+    compare(
+        &to_value(old),
+        &to_value(new),
+        &mut vec![],
+        &mut differences,
+    );
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{json::JsonParser, nix::NixParser, Parser};
+    use crate::transform::diff::diff;
+
+    #[test]
+    fn test_diff() {
+        let old = NixParser::new().parse(OLD).unwrap();
+        let new = JsonParser::new().parse(NEW).unwrap();
+        assert_eq!(
+            diff(&old, &new)
+                .iter()
+                .map(|difference| difference.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "~ programs.mytool.settings.font.size = 11 -> 13",
+                "- programs.mytool.settings.keys = [ \"a\" \"b\" ]",
+                "+ programs.mytool.settings.\"color.scheme\" = \"nord\"",
+                "+ programs.mytool.settings.window.opacity = 0.9",
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    const OLD: &str = "{ config, pkgs, ... }:
+{
+  # Edited by hand
+  programs.mytool.enable = true;
+  programs.mytool.settings = {
+    font = { family = \"Fira Code\"; size = 11; };
+    keys = [ \"a\" \"b\" ];
+  };
+}";
+
+    const NEW: &str = "{
+    \"programs\": { \"mytool\": { \"enable\": true, \"settings\": {
+        \"font\": { \"family\": \"Fira Code\", \"size\": 13 },
+        \"color.scheme\": \"nord\",
+        \"window\": { \"opacity\": 0.9 }
+    } } }
+}";
+}
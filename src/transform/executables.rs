@@ -0,0 +1,249 @@
+use super::{fold, visit_mut, Transform};
+use crate::parser::{attr_name, quote_string, NixVariable, NixVariableValue};
+use indexmap::IndexMap;
+
+/// The bundled index of executables to nixpkgs attributes.
+const EXECUTABLE_INDEX: &str = include_str!("executables.txt");
+
+/// Endings of key names whose values are commands.
+const COMMAND_KEYS: [&str; 12] = [
+    "shell", "editor", "pager", "command", "cmd", "exec", "program", "terminal", "browser",
+    "viewer", "player", "launcher",
+];
+
+/// A package providing an executable.
+#[derive(Debug, Clone, PartialEq)]
+struct Package {
+    attribute: String,
+    main_program: bool,
+}
+
+/// A command that is not in the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unresolved {
+    /// The key path of the value.
+    pub path: Vec<String>,
+    pub command: String,
+}
+
+/// Rewrites commands in string values to the executables of nixpkgs, e.g.
+/// `/usr/bin/zsh` to `"${pkgs.zsh}/bin/zsh"` or `rg --files` to
+/// `"${lib.getExe pkgs.ripgrep} --files"`.
+///
+/// Absolute paths in a `bin` directory are always taken as commands, other
+/// values only if their key ends with a hint at a command, like `editor` or
+/// `defaultShell`.
+/// Executables are looked up in a bundled index.
+pub struct ExecutableDetection {
+    index: IndexMap<String, Package>,
+}
+
+impl Default for ExecutableDetection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutableDetection {
+    pub fn new() -> ExecutableDetection {
+        let mut index = IndexMap::new();
+        for line in EXECUTABLE_INDEX.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut names = line.split_whitespace();
+            let (Some(attribute), Some(main_program)) = (names.next(), names.next()) else {
+                continue;
+            };
+            let package = |main_program| Package {
+                attribute: attribute.to_string(),
+                main_program,
+            };
+            if main_program != "-" {
+                index.insert(main_program.to_string(), package(true));
+            }
+            for name in names {
+                index.insert(name.to_string(), package(false));
+            }
+        }
+        ExecutableDetection { index }
+    }
+
+    /// Splits a value into the executable and its arguments, if it is a
+    /// command.
+    fn command<'a>(key: &str, value: &'a str) -> Option<(&'a str, &'a str)> {
+        if value.contains('\n') {
+            return None;
+        }
+        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+        let (command, arguments) = value.split_at(end);
+        let is_path = command.rsplit_once('/').is_some_and(|(directory, name)| {
+            !name.is_empty() && (directory.ends_with("/bin") || directory.ends_with("/sbin"))
+        });
+        // Flat keys like `terminal.integrated.defaultProfile.linux` are only
+        // judged by their last segment
+        let key = key.rsplit('.').next().unwrap_or(key).to_lowercase();
+        let is_command = COMMAND_KEYS.iter().any(|hint| key.ends_with(hint))
+            && !command.is_empty()
+            && command
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._+-".contains(c));
+        (is_path || is_command).then_some((command, arguments))
+    }
+
+    /// Returns the reference to the executable of a command in its package,
+    /// or `None` if it is not in the index. Paths keep pointing to the same
+    /// file in the package, bare commands use its main program.
+    fn resolve(&self, command: &str, arguments: &str) -> Option<NixVariableValue> {
+        let (directory, executable) = match command.rsplit_once('/') {
+            Some((directory, executable)) => (Some(directory), executable),
+            None => (None, command),
+        };
+        let package = self.index.get(executable)?;
+        let attribute = std::iter::once("pkgs".to_string())
+            .chain(package.attribute.split('.').map(attr_name))
+            .collect::<Vec<String>>()
+            .join(".");
+        let (interpolation, rest) = match (directory, package.main_program) {
+            (None, true) => (format!("lib.getExe {}", attribute), String::new()),
+            (Some(directory), _) if directory.ends_with("/sbin") => {
+                (attribute, format!("/sbin/{}", executable))
+            }
+            _ => (attribute, format!("/bin/{}", executable)),
+        };
+        if rest.is_empty() && arguments.is_empty() {
+            return Some(NixVariableValue::Expression(interpolation));
+        }
+        Some(NixVariableValue::Expression(format!(
+            "\"${{{}}}{}",
+            interpolation,
+            quote_string(&format!("{}{}", rest, arguments)).trim_start_matches('"')
+        )))
+    }
+
+    /// Returns the key of a value, list elements use the key of the list.
+    fn key(path: &[String]) -> &str {
+        path.iter()
+            .rev()
+            .find(|key| key.parse::<usize>().is_err())
+            .map_or("", String::as_str)
+    }
+
+    /// Rewrites the commands and returns those that are not in the index.
+    pub fn detect(&self, values: &[NixVariable]) -> (Vec<NixVariable>, Vec<Unresolved>) {
+        let unresolved = fold(values, vec![], &mut |mut unresolved, path, value| {
+            if let NixVariableValue::String(s) = value {
+                if let Some((command, arguments)) =
+                    ExecutableDetection::command(ExecutableDetection::key(path), s)
+                {
+                    if self.resolve(command, arguments).is_none() {
+                        unresolved.push(Unresolved {
+                            path: path.to_vec(),
+                            command: command.to_string(),
+                        });
+                    }
+                }
+            }
+            unresolved
+        });
+        // The visitor has no key paths, so values are matched by their key
+        // while walking the sets containing them
+        let detected = visit_mut(values, &mut |value| {
+            let resolve = |key: &str, value: &mut NixVariableValue| {
+                if let NixVariableValue::String(s) = value {
+                    if let Some(resolved) = ExecutableDetection::command(key, s)
+                        .and_then(|(command, arguments)| self.resolve(command, arguments))
+                    {
+                        *value = resolved;
+                    }
+                }
+            };
+            if let NixVariableValue::AttributeSet(set) = value {
+                for (key, value) in set.iter_mut() {
+                    match value {
+                        NixVariableValue::List(list) => {
+                            list.iter_mut().for_each(|value| resolve(key, value))
+                        }
+                        value => resolve(key, value),
+                    }
+                }
+            }
+        });
+        (detected, unresolved)
+    }
+}
+
+impl Transform for ExecutableDetection {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(self.detect(values).0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+    use crate::transform::executables::{ExecutableDetection, Unresolved};
+
+    #[test]
+    fn test_executable_detection() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let (detected, unresolved) = ExecutableDetection::new().detect(&parsed);
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&detected),
+            Some(EXPECTED.to_string())
+        );
+        assert_eq!(
+            unresolved,
+            vec![
+                Unresolved {
+                    path: vec!["editor".to_string()],
+                    command: "acme-edit".to_string()
+                },
+                Unresolved {
+                    path: vec!["tools".to_string(), "1".to_string()],
+                    command: "/opt/tools/bin/sync".to_string()
+                },
+            ]
+        );
+    }
+
+    const CONFIG: &str = "{
+    \"editor\": \"acme-edit\",
+    \"finder\": { \"command\": \"rg --files --hidden\" },
+    \"pager\": \"less -R\",
+    \"shell\": \"/usr/bin/zsh\",
+    \"theme\": \"nord\",
+    \"tools\": [\"/usr/local/bin/sh -c true\", \"/opt/tools/bin/sync\"],
+    \"visual\": \"nvim\",
+    \"view\": { \"program\": \"nvim\" },
+    \"vscode\": {
+        \"editor.cursorStyle\": \"line\",
+        \"terminal.external.linuxExec\": \"alacritty\",
+        \"terminal.integrated.defaultProfile.linux\": \"zsh\"
+    }
+}";
+
+    const EXPECTED: &str = "{ config, lib, pkgs, ... }:
+{
+editor = \"acme-edit\";
+finder = {
+command = \"${lib.getExe pkgs.ripgrep} --files --hidden\";
+};
+pager = \"${lib.getExe pkgs.less} -R\";
+shell = \"${pkgs.zsh}/bin/zsh\";
+theme = \"nord\";
+tools = [
+\"${pkgs.bashInteractive}/bin/sh -c true\"
+\"/opt/tools/bin/sync\"
+];
+view = {
+program = lib.getExe pkgs.neovim;
+};
+visual = \"nvim\";
+vscode = {
+\"editor.cursorStyle\" = \"line\";
+\"terminal.external.linuxExec\" = lib.getExe pkgs.alacritty;
+\"terminal.integrated.defaultProfile.linux\" = \"zsh\";
+};
+}";
+}
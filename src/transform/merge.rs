@@ -0,0 +1,226 @@
+use super::{to_value, to_variables};
+use crate::parser::{NixVariable, NixVariableValue};
+use clap::ValueEnum;
+use std::{collections::HashMap, fmt};
+
+/// How lists defined in several files are merged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum ListStrategy {
+    /// The list of the later file replaces the earlier one.
+    replace,
+    /// The elements of the later file are appended.
+    append,
+    /// The elements of the later file missing in the earlier one are
+    /// appended.
+    unique_append,
+}
+
+/// A value defined differently in two files. The later definition is used.
+///
+/// Only the files are known, the parsers keep no line numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// The key path of the value.
+    pub path: Vec<String>,
+    /// The file the value was defined in first.
+    pub first: String,
+    /// The file whose value is used.
+    pub second: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is defined in {} and {}, the latter is used",
+            self.path.join("."),
+            self.first,
+            self.second
+        )
+    }
+}
+
+/// Deep merges configurations split across several files, like a base file
+/// with local overrides or the files of a `conf.d` directory.
+///
+/// Attribute sets are merged key by key. Later files take precedence over
+/// earlier ones, every value they change is reported as a conflict.
+pub struct Merge {
+    lists: ListStrategy,
+}
+
+impl Default for Merge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Merge {
+    pub fn new() -> Merge {
+        Merge {
+            lists: ListStrategy::replace,
+        }
+    }
+
+    pub fn with_lists(mut self, lists: ListStrategy) -> Merge {
+        self.lists = lists;
+        self
+    }
+
+    /// Returns the file that defined the value at a path, which is the file
+    /// that defined it or the attribute set containing it.
+    fn origin(origins: &HashMap<Vec<String>, usize>, path: &[String]) -> usize {
+        (0..=path.len())
+            .rev()
+            .find_map(|length| origins.get(&path[..length]))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn merge_value(
+        &self,
+        value: &mut NixVariableValue,
+        other: &NixVariableValue,
+        path: &mut Vec<String>,
+        source: usize,
+        origins: &mut HashMap<Vec<String>, usize>,
+        conflicts: &mut Vec<(Vec<String>, usize)>,
+    ) {
+        match (value, other) {
+            (NixVariableValue::AttributeSet(set), NixVariableValue::AttributeSet(other)) => {
+                for (key, other) in other {
+                    path.push(key.to_owned());
+                    match set.get_mut(key) {
+                        Some(value) => {
+                            self.merge_value(value, other, path, source, origins, conflicts)
+                        }
+                        None => {
+                            set.insert(key.to_owned(), other.to_owned());
+                            origins.insert(path.to_owned(), source);
+                        }
+                    }
+                    path.pop();
+                }
+            }
+            (NixVariableValue::List(list), NixVariableValue::List(other))
+                if self.lists != ListStrategy::replace =>
+            {
+                for element in other {
+                    if self.lists == ListStrategy::append || !list.contains(element) {
+                        list.push(element.to_owned());
+                    }
+                }
+            }
+            (value, other) => {
+                if value != other {
+                    conflicts.push((path.to_owned(), Merge::origin(origins, path)));
+                    *value = other.to_owned();
+                    origins.insert(path.to_owned(), source);
+                }
+            }
+        }
+    }
+
+    /// Merges the parsed files in order, each given with its name. Returns
+    /// the merged configuration and the values defined differently.
+    pub fn merge(
+        &self,
+        sources: &[(String, Vec<NixVariable>)],
+    ) -> (Vec<NixVariable>, Vec<Conflict>) {
+        let mut merged = to_value(&[]);
+        let mut origins = HashMap::new();
+        let mut conflicts = vec![];
+        for (source, (name, values)) in sources.iter().enumerate() {
+            let mut found = vec![];
+            self.merge_value(
+                &mut merged,
+                &to_value(values),
+                &mut vec![],
+                source,
+                &mut origins,
+                &mut found,
+            );
+            conflicts.extend(found.into_iter().map(|(path, first)| Conflict {
+                path,
+                first: sources[first].0.to_owned(),
+                second: name.to_owned(),
+            }));
+        }
+        (to_variables(merged), conflicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{json::JsonParser, toml::TomlParser, ExpressionGenerator, Parser};
+    use crate::transform::merge::{Conflict, ListStrategy, Merge};
+
+    #[test]
+    fn test_merge() {
+        let sources = vec![
+            (
+                "base.json".to_string(),
+                JsonParser::new().parse(BASE).unwrap(),
+            ),
+            (
+                "local.toml".to_string(),
+                TomlParser::new().parse(LOCAL).unwrap(),
+            ),
+        ];
+        let (merged, conflicts) = Merge::new()
+            .with_lists(ListStrategy::unique_append)
+            .merge(&sources);
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&merged),
+            Some(EXPECTED.to_string())
+        );
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                path: vec!["font".to_string(), "size".to_string()],
+                first: "base.json".to_string(),
+                second: "local.toml".to_string(),
+            }]
+        );
+        assert_eq!(
+            conflicts[0].to_string(),
+            "font.size is defined in base.json and local.toml, the latter is used"
+        );
+        // Replaced lists are conflicts as well
+        let (_, conflicts) = Merge::new().merge(&sources);
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| conflict.path.join("."))
+                .collect::<Vec<String>>(),
+            vec!["font.size", "plugins"]
+        );
+    }
+
+    const BASE: &str = "{
+    \"font\": { \"family\": \"Fira Code\", \"size\": 11 },
+    \"plugins\": [\"git\", \"z\"]
+}";
+
+    const LOCAL: &str = "plugins = [\"git\", \"fzf\"]
+theme = \"nord\"
+
+[font]
+size = 13
+";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+font = {
+family = \"Fira Code\";
+size = 13;
+};
+plugins = [
+\"git\"
+\"z\"
+\"fzf\"
+];
+theme = \"nord\";
+}";
+}
@@ -0,0 +1,185 @@
+//! Transforms of the parsed configuration, applied between parsing and
+//! generating the nix expression.
+pub mod builtin;
+pub mod diff;
+pub mod executables;
+pub mod merge;
+pub mod paths;
+pub mod secrets;
+pub mod selector;
+
+use crate::parser::{NixVariable, NixVariableValue};
+
+pub trait Transform {
+    /// Returns the transformed configuration, or an error explaining why the
+    /// transform can not be applied.
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String>;
+}
+
+/// Applies transforms one after another.
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline { transforms: vec![] }
+    }
+
+    pub fn add_transform(mut self, transform: Box<dyn Transform>) -> Pipeline {
+        self.transforms.push(transform);
+        self
+    }
+}
+
+impl Transform for Pipeline {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        self.transforms
+            .iter()
+            .try_fold(values.to_vec(), |values, transform| {
+                transform.apply(&values)
+            })
+    }
+}
+
+/// Turns the top level variables into an attribute set, so they can be
+/// visited like any other value.
+pub fn to_value(values: &[NixVariable]) -> NixVariableValue {
+    NixVariableValue::AttributeSet(
+        values
+            .iter()
+            .map(|variable| (variable.name.to_owned(), variable.value.to_owned()))
+            .collect(),
+    )
+}
+
+/// Turns an attribute set back into top level variables.
+pub fn to_variables(value: NixVariableValue) -> Vec<NixVariable> {
+    match value {
+        NixVariableValue::AttributeSet(set) => set
+            .into_iter()
+            .map(|(name, value)| NixVariable { name, value })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Calls `visit` on every value of the tree, children before the attribute
+/// sets and lists containing them. The top level is visited as attribute
+/// set last.
+pub fn visit_mut(
+    values: &[NixVariable],
+    visit: &mut dyn FnMut(&mut NixVariableValue),
+) -> Vec<NixVariable> {
+    fn walk(value: &mut NixVariableValue, visit: &mut dyn FnMut(&mut NixVariableValue)) {
+        match value {
+            NixVariableValue::AttributeSet(set) => {
+                set.values_mut().for_each(|value| walk(value, visit))
+            }
+            NixVariableValue::List(list) => list.iter_mut().for_each(|value| walk(value, visit)),
+            NixVariableValue::With(_, value) => walk(value, visit),
+            _ => (),
+        }
+        visit(value);
+    }
+    let mut root = to_value(values);
+    walk(&mut root, visit);
+    to_variables(root)
+}
+
+/// Folds every value of the tree together with its key path, parents before
+/// their children. List elements are keyed by their index.
+pub fn fold<T>(
+    values: &[NixVariable],
+    init: T,
+    f: &mut dyn FnMut(T, &[String], &NixVariableValue) -> T,
+) -> T {
+    fn walk<T>(
+        value: &NixVariableValue,
+        path: &mut Vec<String>,
+        acc: T,
+        f: &mut dyn FnMut(T, &[String], &NixVariableValue) -> T,
+    ) -> T {
+        let mut acc = f(acc, path, value);
+        let children: Vec<(String, &NixVariableValue)> = match value {
+            NixVariableValue::AttributeSet(set) => set
+                .iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+            NixVariableValue::List(list) => list
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (index.to_string(), value))
+                .collect(),
+            _ => vec![],
+        };
+        for (key, child) in children {
+            path.push(key);
+            acc = walk(child, path, acc, f);
+            path.pop();
+        }
+        acc
+    }
+    values.iter().fold(init, |acc, variable| {
+        walk(&variable.value, &mut vec![variable.name.to_owned()], acc, f)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{json::JsonParser, ExpressionGenerator, NixVariableValue, Parser};
+    use crate::transform::{
+        builtin::{BuiltinTransform, RenameKeys},
+        fold, Pipeline, Transform,
+    };
+
+    #[test]
+    fn test_pipeline() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let pipeline = Pipeline::new()
+            .add_transform(BuiltinTransform::DropNulls.transform())
+            .add_transform(Box::new(RenameKeys::new("^Font(.*)$", "font$1").unwrap()))
+            .add_transform(BuiltinTransform::LowercaseKeys.transform())
+            .add_transform(BuiltinTransform::DedupeLists.transform())
+            .add_transform(BuiltinTransform::SortKeys.transform());
+
+        let transformed = pipeline.apply(&parsed).unwrap();
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&transformed),
+            Some(EXPECTED.to_string())
+        );
+        assert_eq!(
+            fold(&transformed, vec![], &mut |mut paths, path, value| {
+                if let NixVariableValue::Number(_) = value {
+                    paths.push(path.join("."));
+                }
+                paths
+            }),
+            vec!["fontsize", "window.padding.0", "window.padding.1"]
+        );
+    }
+
+    const CONFIG: &str = "{
+    \"Window\": { \"Padding\": [4, 4, 8], \"Title\": null, \"Decorations\": \"none\" },
+    \"FontSize\": 12,
+    \"Shell\": null
+}";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+fontsize = 12;
+window = {
+decorations = \"none\";
+padding = [
+4
+8
+];
+};
+}";
+}
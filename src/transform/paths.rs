@@ -0,0 +1,233 @@
+use super::{visit_mut, Transform};
+use crate::converter::Target;
+use crate::parser::{quote_string, NixVariable, NixVariableValue};
+use std::path::{Path, PathBuf};
+
+/// XDG base directories with their default location in the home directory,
+/// their environment variable and their home-manager option.
+const XDG_DIRS: [(&str, &str, &str); 4] = [
+    (".config", "XDG_CONFIG_HOME", "config.xdg.configHome"),
+    (".local/share", "XDG_DATA_HOME", "config.xdg.dataHome"),
+    (".local/state", "XDG_STATE_HOME", "config.xdg.stateHome"),
+    (".cache", "XDG_CACHE_HOME", "config.xdg.cacheHome"),
+];
+
+/// Rewrites strings that are paths in the home directory to references to
+/// `config.home.homeDirectory` or the XDG base directories of home-manager,
+/// so the generated configuration works for every user.
+///
+/// Paths start with `~`, `$HOME`, an XDG variable like `$XDG_CONFIG_HOME`,
+/// the given home directory or any directory in `/home` and `/Users`. Paths
+/// inside the dotfiles repository become nix paths relative to its root,
+/// which is where the generated file is expected to live. NixOS has no
+/// home directory options, so for it only dotfiles are rewritten.
+pub struct PathDetection {
+    home: Option<PathBuf>,
+    dotfiles: Option<PathBuf>,
+    target: Target,
+}
+
+impl Default for PathDetection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathDetection {
+    pub fn new() -> PathDetection {
+        PathDetection {
+            home: None,
+            dotfiles: None,
+            target: Target::home_manager,
+        }
+    }
+
+    pub fn with_target(mut self, target: Target) -> PathDetection {
+        self.target = target;
+        self
+    }
+
+    /// The home directory of the user the configuration was taken from.
+    pub fn with_home(mut self, home: &Path) -> PathDetection {
+        self.home = Some(home.to_path_buf());
+        self
+    }
+
+    /// The repository the generated file is written to.
+    pub fn with_dotfiles(mut self, dotfiles: &Path) -> PathDetection {
+        self.dotfiles = Some(dotfiles.to_path_buf());
+        self
+    }
+
+    /// Strips a variable like `$HOME` or `${HOME}` from the start of a path.
+    fn strip_variable<'a>(value: &'a str, variable: &str) -> Option<&'a str> {
+        value
+            .strip_prefix(&format!("${}", variable))
+            .or_else(|| value.strip_prefix(&format!("${{{}}}", variable)))
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// Returns the part of a path after the home directory.
+    fn home_relative<'a>(&self, value: &'a str) -> Option<&'a str> {
+        let rest = value
+            .strip_prefix('~')
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+            .or_else(|| PathDetection::strip_variable(value, "HOME"))
+            .or_else(|| {
+                let home = self.home.as_ref()?.to_str()?.trim_end_matches('/');
+                value
+                    .strip_prefix(home)
+                    .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .or_else(|| {
+                // The home of any user, e.g. /home/jane/.vimrc
+                let user = ["/home/", "/Users/"]
+                    .iter()
+                    .find_map(|home| value.strip_prefix(home))?;
+                match user.split_once('/') {
+                    Some((name, _)) if !name.is_empty() => Some(&user[name.len()..]),
+                    None if !user.is_empty() => Some(""),
+                    _ => None,
+                }
+            })?;
+        Some(rest.trim_start_matches('/'))
+    }
+
+    /// Returns an interpolation of a path below an option.
+    fn reference(option: &str, rest: &str) -> NixVariableValue {
+        let rest = rest.trim_matches('/');
+        if rest.is_empty() {
+            return NixVariableValue::Expression(option.to_string());
+        }
+        let escaped = quote_string(rest);
+        NixVariableValue::Expression(format!(
+            "\"${{{}}}/{}",
+            option,
+            escaped.trim_start_matches('"')
+        ))
+    }
+
+    /// Returns a nix path relative to the dotfiles repository.
+    fn dotfile(&self, value: &str) -> Option<NixVariableValue> {
+        let dotfiles = self.dotfiles.as_ref()?;
+        let path = match (self.home_relative(value), &self.home) {
+            (Some(rest), Some(home)) => home.join(rest),
+            (Some(_), None) => return None,
+            (None, _) => PathBuf::from(value),
+        };
+        let rest = path.strip_prefix(dotfiles).ok()?.to_str()?;
+        if rest.is_empty() {
+            return Some(NixVariableValue::Path(Box::new(PathBuf::from("./."))));
+        }
+        if rest
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-+/".contains(c))
+        {
+            Some(NixVariableValue::Path(Box::new(PathBuf::from(format!(
+                "./{}",
+                rest
+            )))))
+        } else {
+            Some(NixVariableValue::Expression(format!(
+                "./. + {}",
+                quote_string(&format!("/{}", rest))
+            )))
+        }
+    }
+
+    fn detect(&self, value: &str) -> Option<NixVariableValue> {
+        if value.contains('\n') {
+            return None;
+        }
+        if let Some(path) = self.dotfile(value) {
+            return Some(path);
+        }
+        if self.target != Target::home_manager {
+            return None;
+        }
+        for (_, variable, option) in XDG_DIRS {
+            if let Some(rest) = PathDetection::strip_variable(value, variable) {
+                return Some(PathDetection::reference(option, rest));
+            }
+        }
+        let rest = self.home_relative(value)?;
+        for (directory, _, option) in XDG_DIRS {
+            if let Some(rest) = rest.strip_prefix(directory) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    return Some(PathDetection::reference(option, rest));
+                }
+            }
+        }
+        Some(PathDetection::reference("config.home.homeDirectory", rest))
+    }
+}
+
+impl Transform for PathDetection {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        Ok(visit_mut(values, &mut |value| {
+            if let NixVariableValue::String(s) = value {
+                if let Some(path) = self.detect(s) {
+                    *value = path;
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::Target;
+    use crate::parser::{json::JsonParser, ExpressionGenerator, NixVariableValue, Parser};
+    use crate::transform::{paths::PathDetection, Transform};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_path_detection() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+        let detected = PathDetection::new()
+            .with_home(Path::new("/home/jane"))
+            .with_dotfiles(Path::new("/home/jane/dotfiles"))
+            .apply(&parsed)
+            .unwrap();
+        assert_eq!(
+            ExpressionGenerator::new().generate_nix_module(&detected),
+            Some(EXPECTED.to_string())
+        );
+        let detected = PathDetection::new()
+            .with_home(Path::new("/home/jane"))
+            .with_dotfiles(Path::new("/home/jane/dotfiles"))
+            .with_target(Target::nixos)
+            .apply(&parsed)
+            .unwrap();
+        assert_eq!(detected[0], parsed[0]);
+        assert_eq!(
+            detected[6].value,
+            NixVariableValue::Path(Box::new(PathBuf::from("./themes/nord.toml")))
+        );
+    }
+
+    const CONFIG: &str = "{
+    \"cache\": \"$XDG_CACHE_HOME/mytool\",
+    \"config\": \"~/.config\",
+    \"data\": \"/home/jane/.local/share/mytool/${name}.db\",
+    \"home\": \"${HOME}\",
+    \"notes\": \"/Users/john/My Notes\",
+    \"system\": \"/etc/mytool.conf\",
+    \"theme\": \"~/dotfiles/themes/nord.toml\",
+    \"wallpaper\": \"/home/jane/dotfiles/My Wallpaper.png\",
+    \"word\": \"~user\"
+}";
+
+    const EXPECTED: &str = "{ config, pkgs, ... }:
+{
+cache = \"${config.xdg.cacheHome}/mytool\";
+config = config.xdg.configHome;
+data = \"${config.xdg.dataHome}/mytool/\\${name}.db\";
+home = config.home.homeDirectory;
+notes = \"${config.home.homeDirectory}/My Notes\";
+system = \"/etc/mytool.conf\";
+theme = ./themes/nord.toml;
+wallpaper = ./. + \"/My Wallpaper.png\";
+word = \"~user\";
+}";
+}
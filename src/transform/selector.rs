@@ -0,0 +1,466 @@
+use super::{to_value, to_variables, Transform};
+use crate::parser::{NixVariable, NixVariableValue};
+use indexmap::IndexMap;
+use std::{fmt, str::FromStr};
+
+/// A segment of a selector.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// A key of an attribute set, e.g. `foo` or `"editor.fontSize"`.
+    Key(String),
+    /// An element of a list, e.g. `[2]`.
+    Index(usize),
+    /// Every key of an attribute set or element of a list, `*` or `[*]`.
+    Wildcard,
+    /// Any number of levels, including none, `**`.
+    Recursive,
+}
+
+/// A step of the path to a value in the parsed configuration.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// Selects values in the parsed configuration by their key path.
+///
+/// Keys are separated by dots, keys containing dots are quoted, e.g.
+/// `"[rust]"."editor.formatOnSave"`. List elements are selected with `[2]`,
+/// `*` selects every key or element of one level and `**` any number of
+/// levels, e.g. `**.password`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selector::parse(s).ok_or_else(|| format!("invalid selector `{}`", s))
+    }
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Option<Selector> {
+        let mut segments = vec![];
+        let mut chars = selector.chars().peekable();
+        loop {
+            match chars.peek()? {
+                '"' => {
+                    chars.next();
+                    let mut key = String::new();
+                    loop {
+                        match chars.next()? {
+                            '"' => break,
+                            '\\' => key.push(chars.next()?),
+                            c => key.push(c),
+                        }
+                    }
+                    segments.push(Segment::Key(key));
+                }
+                '[' => (),
+                _ => {
+                    let mut key = String::new();
+                    while let Some(c) = chars.next_if(|c| !".[\"".contains(*c)) {
+                        key.push(c);
+                    }
+                    segments.push(match key.as_str() {
+                        "" => return None,
+                        "*" => Segment::Wildcard,
+                        "**" => Segment::Recursive,
+                        _ => Segment::Key(key),
+                    });
+                }
+            }
+            while chars.next_if_eq(&'[').is_some() {
+                let mut index = String::new();
+                while let Some(c) = chars.next_if(|c| *c != ']') {
+                    index.push(c);
+                }
+                chars.next()?;
+                segments.push(match index.as_str() {
+                    "*" => Segment::Wildcard,
+                    index => Segment::Index(index.parse().ok()?),
+                });
+            }
+            match chars.next() {
+                None => break,
+                Some('.') => continue,
+                Some(_) => return None,
+            }
+        }
+        Some(Selector {
+            source: selector.to_string(),
+            segments,
+        })
+    }
+
+    /// Collects the paths of all values the segments match.
+    fn collect(
+        segments: &[Segment],
+        value: &NixVariableValue,
+        path: &mut Vec<Step>,
+        paths: &mut Vec<Vec<Step>>,
+    ) {
+        let Some((segment, rest)) = segments.split_first() else {
+            if !paths.contains(path) {
+                paths.push(path.to_owned());
+            }
+            return;
+        };
+        let children: Vec<(Step, &NixVariableValue)> = match value {
+            NixVariableValue::AttributeSet(set) => set
+                .iter()
+                .map(|(key, value)| (Step::Key(key.to_owned()), value))
+                .collect(),
+            NixVariableValue::List(list) => list
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (Step::Index(index), value))
+                .collect(),
+            _ => vec![],
+        };
+        if *segment == Segment::Recursive {
+            Selector::collect(rest, value, path, paths);
+        }
+        for (step, child) in children {
+            let next = match (segment, &step) {
+                (Segment::Key(key), Step::Key(k)) if key == k => rest,
+                (Segment::Index(index), Step::Index(i)) if index == i => rest,
+                (Segment::Wildcard, _) => rest,
+                (Segment::Recursive, _) => segments,
+                _ => continue,
+            };
+            path.push(step);
+            Selector::collect(next, child, path, paths);
+            path.pop();
+        }
+    }
+
+    /// Returns the paths of the values matched in the configuration.
+    fn paths(&self, root: &NixVariableValue) -> Vec<Vec<Step>> {
+        let mut paths = vec![];
+        Selector::collect(&self.segments, root, &mut vec![], &mut paths);
+        paths
+    }
+
+    /// Calls `visit` on every value the selector matches, outer values before
+    /// the values they contain. Returns an error if it matches nothing.
+    pub fn visit_mut(
+        &self,
+        values: &[NixVariable],
+        visit: &mut dyn FnMut(&mut NixVariableValue),
+    ) -> Result<Vec<NixVariable>, String> {
+        let mut root = to_value(values);
+        let mut paths = matched_paths(std::slice::from_ref(self), &root)?;
+        paths.sort_by_key(|path| path.len());
+        for path in paths {
+            if let Some(value) = at(&mut root, &path) {
+                visit(value);
+            }
+        }
+        Ok(to_variables(root))
+    }
+
+    /// Like [`Selector::visit_mut`], but the names of the variables are
+    /// option paths like `programs.alacritty.settings`, whose keys are
+    /// matched like the keys of attribute sets. A selector matching a part
+    /// of an option path visits the whole value of the option.
+    pub fn visit_options_mut(
+        &self,
+        values: &[NixVariable],
+        visit: &mut dyn FnMut(&mut NixVariableValue),
+    ) -> Result<Vec<NixVariable>, String> {
+        let options: Vec<(Vec<String>, NixVariableValue, Vec<Vec<Step>>)> = values
+            .iter()
+            .map(|variable| {
+                let keys = option_keys(&variable.name);
+                let root = keys
+                    .iter()
+                    .rev()
+                    .fold(variable.value.to_owned(), |value, key| {
+                        NixVariableValue::AttributeSet(IndexMap::from([(key.to_owned(), value)]))
+                    });
+                let mut paths = vec![];
+                for path in self.paths(&root) {
+                    let path = match path.len() < keys.len() {
+                        true => keys.iter().map(|key| Step::Key(key.to_owned())).collect(),
+                        false => path,
+                    };
+                    if !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+                paths.sort_by_key(|path| path.len());
+                (keys, root, paths)
+            })
+            .collect();
+        if options.iter().all(|(_, _, paths)| paths.is_empty()) {
+            return Err(format!("selector `{}` matches nothing", self));
+        }
+        Ok(values
+            .iter()
+            .zip(options)
+            .map(|(variable, (keys, mut root, paths))| {
+                for path in paths {
+                    if let Some(value) = at(&mut root, &path) {
+                        visit(value);
+                    }
+                }
+                // Only the values of options are visited, so their path is kept
+                let path: Vec<Step> = keys.into_iter().map(Step::Key).collect();
+                let value = at(&mut root, &path).expect("option paths are not visited");
+                NixVariable::new(&variable.name, value)
+            })
+            .collect())
+    }
+
+    /// The selector matching the same paths below the values `parent`
+    /// matches, e.g. `font.size` below `**.settings`. Errors keep naming the
+    /// selector itself.
+    pub fn below(&self, parent: &Selector) -> Selector {
+        Selector {
+            source: self.source.to_owned(),
+            segments: parent
+                .segments
+                .iter()
+                .chain(&self.segments)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// The keys of an option path, e.g. `programs`, `alacritty` and `settings`
+/// for `programs.alacritty.settings`. Names that are no option path are a
+/// single key.
+fn option_keys(name: &str) -> Vec<String> {
+    Selector::parse(name)
+        .and_then(|selector| {
+            selector
+                .segments
+                .into_iter()
+                .map(|segment| match segment {
+                    Segment::Key(key) => Some(key),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_else(|| vec![name.to_string()])
+}
+
+/// Returns the value at a path.
+fn at<'a>(value: &'a mut NixVariableValue, path: &[Step]) -> Option<&'a mut NixVariableValue> {
+    let Some((step, rest)) = path.split_first() else {
+        return Some(value);
+    };
+    match (value, step) {
+        (NixVariableValue::AttributeSet(set), Step::Key(key)) => at(set.get_mut(key)?, rest),
+        (NixVariableValue::List(list), Step::Index(index)) => at(list.get_mut(*index)?, rest),
+        _ => None,
+    }
+}
+
+/// Returns the paths matched by every selector, or an error naming the first
+/// selector that matches nothing.
+fn matched_paths(
+    selectors: &[Selector],
+    root: &NixVariableValue,
+) -> Result<Vec<Vec<Step>>, String> {
+    let mut paths = vec![];
+    for selector in selectors {
+        let matched = selector.paths(root);
+        if matched.is_empty() {
+            return Err(format!("selector `{}` matches nothing", selector));
+        }
+        paths.extend(matched);
+    }
+    Ok(paths)
+}
+
+/// Keeps or drops the values at the matched paths.
+fn prune(
+    value: &NixVariableValue,
+    path: &mut Vec<Step>,
+    paths: &[Vec<Step>],
+    include: bool,
+) -> Option<NixVariableValue> {
+    if paths.iter().any(|p| path.starts_with(p)) {
+        return include.then(|| value.to_owned());
+    }
+    if include && !paths.iter().any(|p| p.starts_with(path)) {
+        return None;
+    }
+    let mut child = |step: Step, value: &NixVariableValue| {
+        path.push(step);
+        let pruned = prune(value, path, paths, include);
+        path.pop();
+        pruned
+    };
+    Some(match value {
+        NixVariableValue::AttributeSet(set) => NixVariableValue::AttributeSet(
+            set.iter()
+                .filter_map(|(key, value)| {
+                    Some((key.to_owned(), child(Step::Key(key.to_owned()), value)?))
+                })
+                .collect(),
+        ),
+        NixVariableValue::List(list) => NixVariableValue::List(
+            list.iter()
+                .enumerate()
+                .filter_map(|(index, value)| child(Step::Index(index), value))
+                .collect(),
+        ),
+        value => value.to_owned(),
+    })
+}
+
+fn filter(
+    values: &[NixVariable],
+    selectors: &[Selector],
+    include: bool,
+) -> Result<Vec<NixVariable>, String> {
+    let root = to_value(values);
+    let paths = matched_paths(selectors, &root)?;
+    Ok(prune(&root, &mut vec![], &paths, include)
+        .map(to_variables)
+        .unwrap_or_default())
+}
+
+/// Keeps only the values matched by any of the selectors, together with the
+/// attribute sets and lists containing them.
+pub struct Include {
+    selectors: Vec<Selector>,
+}
+
+impl Include {
+    pub fn new(selectors: &[Selector]) -> Include {
+        Include {
+            selectors: selectors.to_vec(),
+        }
+    }
+}
+
+impl Transform for Include {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        filter(values, &self.selectors, true)
+    }
+}
+
+/// Drops the values matched by any of the selectors.
+pub struct Exclude {
+    selectors: Vec<Selector>,
+}
+
+impl Exclude {
+    pub fn new(selectors: &[Selector]) -> Exclude {
+        Exclude {
+            selectors: selectors.to_vec(),
+        }
+    }
+}
+
+impl Transform for Exclude {
+    fn apply(&self, values: &[NixVariable]) -> Result<Vec<NixVariable>, String> {
+        filter(values, &self.selectors, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{json::JsonParser, ExpressionGenerator, Parser};
+    use crate::transform::{
+        selector::{Exclude, Include, Segment, Selector},
+        Transform,
+    };
+
+    fn selectors(selectors: &[&str]) -> Vec<Selector> {
+        selectors
+            .iter()
+            .map(|selector| selector.parse().unwrap())
+            .collect()
+    }
+
+    fn generate(result: Result<Vec<crate::parser::NixVariable>, String>) -> String {
+        ExpressionGenerator::new()
+            .generate_nix_module(&result.unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_selector_parsing() {
+        assert_eq!(
+            Selector::parse("**.\"editor.rulers\"[1].*[*]").map(|s| s.segments),
+            Some(vec![
+                Segment::Recursive,
+                Segment::Key("editor.rulers".to_string()),
+                Segment::Index(1),
+                Segment::Wildcard,
+                Segment::Wildcard,
+            ])
+        );
+        assert!(Selector::parse("foo..bar").is_none());
+        assert!(Selector::parse("foo[bar]").is_none());
+        assert!(Selector::parse("").is_none());
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let parsed = JsonParser::new().parse(CONFIG).unwrap();
+
+        assert_eq!(
+            generate(Include::new(&selectors(&["servers.*.name", "list[1]"])).apply(&parsed)),
+            "{ config, pkgs, ... }:
+{
+list = [
+2
+];
+servers = {
+a = {
+name = \"alpha\";
+};
+b = {
+name = \"beta\";
+};
+};
+}"
+        );
+        assert_eq!(
+            generate(Exclude::new(&selectors(&["**.password", "list"])).apply(&parsed)),
+            "{ config, pkgs, ... }:
+{
+servers = {
+a = {
+name = \"alpha\";
+};
+b = {
+name = \"beta\";
+port = 22;
+};
+};
+}"
+        );
+        assert_eq!(
+            Include::new(&selectors(&["servers.c"])).apply(&parsed),
+            Err("selector `servers.c` matches nothing".to_string())
+        );
+    }
+
+    const CONFIG: &str = "{
+    \"password\": \"hunter2\",
+    \"list\": [1, 2, 3],
+    \"servers\": {
+        \"a\": { \"name\": \"alpha\", \"password\": \"secret\" },
+        \"b\": { \"name\": \"beta\", \"port\": 22 }
+    }
+}";
+}